@@ -0,0 +1,211 @@
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait BondingCurveModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
+{
+    // buys tokens from the curve with base tokens. the pair fee is charged on the payment: the owner part is
+    // accumulated as usual, the LP part stays in the raised base and ends up in the pool
+    #[payable("*")]
+    #[endpoint(buyBondingCurve)]
+    fn buy_bonding_curve(&self, pair_id: usize, min_tokens_out: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+
+        let payment = self.get_single_payment();
+        let (mut pair, mut curve) = self.get_bonding_curve_pair(pair_id);
+        require!(payment.token_identifier == pair.base_token, ERROR_WRONG_PAYMENT);
+
+        let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(pair_id, &payment.amount, true);
+        let base_amount = &payment.amount - &total_fee;
+        let token_amount = self.get_bonding_curve_tokens_out(&curve, &base_amount);
+        require!(token_amount > 0 && token_amount >= min_tokens_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+        require!(&curve.tokens_sold + &token_amount <= curve.sale_supply, ERROR_BONDING_CURVE_SOLD_OUT);
+
+        self.add_cummulated_fee(&pair.base_token, &owner_fee);
+        curve.tokens_sold += &token_amount;
+        curve.base_raised += &base_amount + &lp_fee;
+
+        let caller = self.blockchain().get_caller();
+        self.send_payout(&caller, &pair.token, &token_amount);
+        self.bonding_curve_buy_event(pair_id, &caller, &BondingCurveTradeEvent {
+            base_amount: payment.amount,
+            token_amount,
+            lp_fee,
+            owner_fee,
+            tokens_sold: curve.tokens_sold.clone(),
+            base_raised: curve.base_raised.clone(),
+        });
+
+        if curve.base_raised >= curve.target_base {
+            self.graduate_bonding_curve(&mut pair, &curve);
+        } else {
+            self.bonding_curves(pair_id).set(&curve);
+        }
+    }
+
+    // sells tokens back to the curve, the pair fee being charged on the base paid out
+    #[payable("*")]
+    #[endpoint(sellBondingCurve)]
+    fn sell_bonding_curve(&self, pair_id: usize, min_base_out: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+
+        let payment = self.call_value().single_esdt();
+        let (pair, mut curve) = self.get_bonding_curve_pair(pair_id);
+        require!(payment.token_identifier == pair.token, ERROR_WRONG_PAYMENT);
+        require!(payment.amount > 0 && payment.amount <= curve.tokens_sold, ERROR_WRONG_PAYMENT);
+
+        let gross_amount = self.get_bonding_curve_sell_amount(&curve, &payment.amount);
+        let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(pair_id, &gross_amount, true);
+        let base_amount = &gross_amount - &total_fee;
+        require!(base_amount > 0 && base_amount >= min_base_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+        self.add_cummulated_fee(&pair.base_token, &owner_fee);
+        curve.tokens_sold -= &payment.amount;
+        curve.base_raised -= &gross_amount - &lp_fee;
+        self.bonding_curves(pair_id).set(&curve);
+
+        let caller = self.blockchain().get_caller();
+        self.send_payout(&caller, &pair.base_token, &base_amount);
+        self.bonding_curve_sell_event(pair_id, &caller, &BondingCurveTradeEvent {
+            base_amount,
+            token_amount: payment.amount,
+            lp_fee,
+            owner_fee,
+            tokens_sold: curve.tokens_sold,
+            base_raised: curve.base_raised,
+        });
+    }
+
+    // seeds the pair with the raised base and the reserved tokens. the LP tokens stay in the contract forever
+    // and tokens left unsold go back to the creator of the curve
+    fn graduate_bonding_curve(&self, pair: &mut Pair<Self::Api>, curve: &BondingCurve<Self::Api>) {
+        let unsold = &curve.sale_supply - &curve.tokens_sold;
+        if unsold > 0 {
+            self.send_payout(&curve.creator, &pair.token, &unsold);
+        }
+
+        self.update_price_cumulatives(pair);
+        let lp_amount = (&curve.reserved_tokens * &curve.base_raised).sqrt();
+        pair.liquidity_token = curve.reserved_tokens.clone();
+        pair.liquidity_base = curve.base_raised.clone();
+        pair.lp_supply = lp_amount.clone();
+        pair.state = PairState::Active;
+        self.pairs(pair.id).set(&*pair);
+        self.bonding_curves(pair.id).clear();
+
+        self.send().esdt_local_mint(&pair.lp_token, 0, &lp_amount);
+        self.pair_state_changed_event(pair.id, pair.state);
+        self.bonding_curve_graduated_event(pair.id, &LiquidityEvent {
+            token_amount: curve.reserved_tokens.clone(),
+            base_amount: curve.base_raised.clone(),
+            lp_amount: lp_amount.clone(),
+            lp_supply: lp_amount,
+            liquidity_token: pair.liquidity_token.clone(),
+            liquidity_base: pair.liquidity_base.clone(),
+        });
+    }
+
+    fn get_bonding_curve_pair(&self, pair_id: usize) -> (Pair<Self::Api>, BondingCurve<Self::Api>) {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pairs(pair_id).get();
+        require!(pair.state == PairState::BondingCurve, ERROR_PAIR_NOT_IN_BONDING_CURVE);
+
+        (pair, self.bonding_curves(pair_id).get())
+    }
+
+    // base needed to move the curve from `from` to `to` tokens sold
+    fn get_bonding_curve_cost(&self, curve: &BondingCurve<Self::Api>, from: &BigUint, to: &BigUint) -> BigUint {
+        let amount = to - from;
+        match curve.curve_type {
+            BondingCurveType::Linear => {
+                let slope_part = &curve.price_param * &(to * to - from * from) / (2 * PRICE_PRECISION);
+
+                (&curve.initial_price * &amount + slope_part) / PRICE_PRECISION
+            },
+            BondingCurveType::Exponential => {
+                let one = BigUint::from(FIXED_POINT_ONE);
+                let exp_to = self.exp_fixed_point(&(to * &one / &curve.price_param));
+                let exp_from = self.exp_fixed_point(&(from * &one / &curve.price_param));
+
+                &curve.initial_price * &curve.price_param * (exp_to - exp_from) / FIXED_POINT_ONE / PRICE_PRECISION
+            },
+        }
+    }
+
+    // base paid for selling token_amount back, before fees. buys round in favour of the curve one step at a time,
+    // so the integral over several steps can still end up above what was raised: the sale is capped to base_raised
+    fn get_bonding_curve_sell_amount(&self, curve: &BondingCurve<Self::Api>, token_amount: &BigUint) -> BigUint {
+        let cost = self.get_bonding_curve_cost(curve, &(&curve.tokens_sold - token_amount), &curve.tokens_sold);
+
+        core::cmp::min(cost, curve.base_raised.clone())
+    }
+
+    // tokens the curve sells for base_amount from its current point
+    fn get_bonding_curve_tokens_out(&self, curve: &BondingCurve<Self::Api>, base_amount: &BigUint) -> BigUint {
+        let precision = BigUint::from(PRICE_PRECISION);
+        let sold = &curve.tokens_sold;
+        let token_amount = match curve.curve_type {
+            BondingCurveType::Linear => {
+                let price = &curve.initial_price + &(&curve.price_param * sold / &precision);
+                if curve.price_param == 0 {
+                    base_amount * &precision / price
+                } else {
+                    // root of price_param * d^2 + 2 * PRICE_PRECISION * price * d - 2 * PRICE_PRECISION^2 * base = 0
+                    let root = (&price * &price + &curve.price_param * base_amount * 2u64).sqrt();
+
+                    precision * (root - price) / &curve.price_param
+                }
+            },
+            BondingCurveType::Exponential => {
+                let one = BigUint::from(FIXED_POINT_ONE);
+                let exp_from = self.exp_fixed_point(&(sold * &one / &curve.price_param));
+                let exp_to = exp_from + base_amount * &precision * &one / (&curve.initial_price * &curve.price_param);
+                let new_sold = self.ln_fixed_point(&exp_to) * &curve.price_param / &one;
+                if &new_sold <= sold {
+                    return BigUint::zero();
+                }
+
+                new_sold - sold
+            },
+        };
+        if token_amount == 0 {
+            return token_amount;
+        }
+
+        // rounding must never sell more than what was paid for. the cost is convex and zero for no tokens,
+        // so shrinking the amount in proportion brings it back under base_amount
+        let cost = self.get_bonding_curve_cost(curve, sold, &(sold + &token_amount));
+        if &cost > base_amount {
+            return token_amount * base_amount / cost;
+        }
+
+        token_amount
+    }
+
+    #[view(getBondingCurveBuyQuote)]
+    fn get_bonding_curve_buy_quote(&self, pair_id: usize, base_amount: BigUint) -> BigUint {
+        let (_, curve) = self.get_bonding_curve_pair(pair_id);
+        let (_, _, total_fee) = self.get_fee_amounts(pair_id, &base_amount, true);
+
+        self.get_bonding_curve_tokens_out(&curve, &(base_amount - total_fee))
+    }
+
+    #[view(getBondingCurveSellQuote)]
+    fn get_bonding_curve_sell_quote(&self, pair_id: usize, token_amount: BigUint) -> BigUint {
+        let (_, curve) = self.get_bonding_curve_pair(pair_id);
+        require!(token_amount <= curve.tokens_sold, ERROR_BONDING_CURVE_SOLD_OUT);
+
+        let gross_amount = self.get_bonding_curve_sell_amount(&curve, &token_amount);
+        let (_, _, total_fee) = self.get_fee_amounts(pair_id, &gross_amount, true);
+
+        gross_amount - total_fee
+    }
+}
@@ -1,8 +1,8 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
-use crate::common::{errors::*, consts::*};
-use crate::proxies::launchpad_proxy::{self};
+use crate::common::{errors::*, consts::*, events};
+use crate::proxies::{launchpad_proxy, wrapper_proxy};
 
 #[type_abi]
 #[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
@@ -17,6 +17,22 @@ pub enum PairState {
     Inactive,
     ActiveNoSwap,
     Active,
+    BondingCurve,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum LpRolesState {
+    Pending,
+    Granted,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CurveType {
+    ConstantProduct,
+    StableSwap,
+    Weighted,
 }
 
 #[type_abi]
@@ -32,8 +48,204 @@ pub struct Pair<M: ManagedTypeApi> {
     pub liquidity_base: BigUint<M>,
 }
 
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct PairFees {
+    pub lp_fee: u64,
+    pub owner_fee: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct PairWeights {
+    pub weight_token: u64,
+    pub weight_base: u64,
+}
+
+// amplification moves linearly from initial_amp to future_amp between initial_time and future_time
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct AmpRamp {
+    pub initial_amp: u64,
+    pub future_amp: u64,
+    pub initial_time: u64,
+    pub future_time: u64,
+}
+
+// the stable curve runs on reserves multiplied by these, bringing both tokens to the same number of decimals
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct PrecisionMultipliers {
+    pub token_multiplier: u64,
+    pub base_multiplier: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum BondingCurveType {
+    Linear,
+    Exponential,
+}
+
+// pre-AMM sale of a pair's token. prices are in base per token, scaled by PRICE_PRECISION:
+//   linear:      price(sold) = initial_price + price_param * sold / PRICE_PRECISION
+//   exponential: price(sold) = initial_price * e ^ (sold / price_param)
+// once base_raised reaches target_base the pair is seeded with base_raised and reserved_tokens
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct BondingCurve<M: ManagedTypeApi> {
+    pub curve_type: BondingCurveType,
+    pub creator: ManagedAddress<M>,
+    pub initial_price: BigUint<M>,
+    pub price_param: BigUint<M>,
+    pub sale_supply: BigUint<M>,
+    pub reserved_tokens: BigUint<M>,
+    pub target_base: BigUint<M>,
+    pub tokens_sold: BigUint<M>,
+    pub base_raised: BigUint<M>,
+}
+
+// liquidity bootstrapping: the token weight moves linearly from start_weight_token to end_weight_token
+// between start_time and end_time, the base weight being the rest up to MAX_PERCENT
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct LbpSchedule {
+    pub start_weight_token: u64,
+    pub end_weight_token: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+// concentrated liquidity pool. sqrt_price is the square root of the base per token price, scaled by
+// FIXED_POINT_ONE, and liquidity the one of the positions whose range holds the current tick. fee growths
+// are the LP fees earned per unit of liquidity since creation and the reserves include uncollected fees
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClPool<M: ManagedTypeApi> {
+    pub id: usize,
+    pub token: TokenIdentifier<M>,
+    pub base_token: TokenIdentifier<M>,
+    pub tick_spacing: i64,
+    pub sqrt_price: BigUint<M>,
+    pub current_tick: i64,
+    pub liquidity: BigUint<M>,
+    pub fee_growth_token: BigUint<M>,
+    pub fee_growth_base: BigUint<M>,
+    pub reserve_token: BigUint<M>,
+    pub reserve_base: BigUint<M>,
+}
+
+// fee growths outside a tick are the ones on the other side of it from the current tick
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClTick<M: ManagedTypeApi> {
+    pub liquidity_gross: BigUint<M>,
+    pub liquidity_net: BigInt<M>,
+    pub fee_growth_outside_token: BigUint<M>,
+    pub fee_growth_outside_base: BigUint<M>,
+}
+
+// attributes of a position NFT. the fee growths inside the range are taken when the position was minted
+// or its fees last collected, and can be negative since they are only meaningful as differences
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClPosition<M: ManagedTypeApi> {
+    pub pool_id: usize,
+    pub tick_lower: i64,
+    pub tick_upper: i64,
+    pub liquidity: BigUint<M>,
+    pub fee_growth_inside_token: BigInt<M>,
+    pub fee_growth_inside_base: BigInt<M>,
+}
+
+// sells amount_in of token_in on pair_id as soon as it returns at least min_amount_out of token_out
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct LimitOrder<M: ManagedTypeApi> {
+    pub id: u64,
+    pub owner: ManagedAddress<M>,
+    pub pair_id: usize,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub min_amount_out: BigUint<M>,
+    pub created_at: u64,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ConditionalOrderType {
+    StopLoss,
+    TakeProfit,
+}
+
+// sells amount_in of token_in once its TWAP in token_out falls to (stop loss) or rises to (take profit)
+// trigger_price, until expires_at
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ConditionalOrder<M: ManagedTypeApi> {
+    pub id: u64,
+    pub owner: ManagedAddress<M>,
+    pub pair_id: usize,
+    pub order_type: ConditionalOrderType,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub trigger_price: BigUint<M>,
+    pub twap_window: u64,
+    pub max_slippage: u64,
+    pub expires_at: u64,
+}
+
+// recurring swap of amount_per_swap every interval seconds, until remaining runs out. the bought tokens
+// pile up in accumulated until claimed
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct DcaSchedule<M: ManagedTypeApi> {
+    pub id: u64,
+    pub owner: ManagedAddress<M>,
+    pub pair_id: usize,
+    pub token_in: TokenIdentifier<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub amount_per_swap: BigUint<M>,
+    pub interval: u64,
+    pub min_price: BigUint<M>,
+    pub remaining: BigUint<M>,
+    pub accumulated: BigUint<M>,
+    pub last_execution: u64,
+}
+
+// reward emissions for the stakers of a pair's LP token: reward_per_second between start_time and end_time,
+// split through reward_per_share (scaled by FIXED_POINT_ONE). funded and distributed track the reward tokens
+// deposited and the ones already accrued to stakers
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct Farm<M: ManagedTypeApi> {
+    pub pair_id: usize,
+    pub reward_token: TokenIdentifier<M>,
+    pub reward_per_second: BigUint<M>,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub total_staked: BigUint<M>,
+    pub reward_per_share: BigUint<M>,
+    pub last_update: u64,
+    pub funded: BigUint<M>,
+    pub distributed: BigUint<M>,
+}
+
+// reward_debt is the part of amount * reward_per_share already accounted for in pending
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct FarmPosition<M: ManagedTypeApi> {
+    pub amount: BigUint<M>,
+    pub reward_debt: BigUint<M>,
+    pub pending: BigUint<M>,
+}
+
 #[multiversx_sc::module]
-pub trait ConfigModule {
+pub trait ConfigModule:
+events::EventsModule
+{
     // state
     #[only_owner]
     #[endpoint(setStateActive)]
@@ -71,11 +283,31 @@ pub trait ConfigModule {
             .governance_token()
             .execute_on_dest_context();
         if !self.base_tokens().contains(&governance_token) {
-            self.base_tokens().insert(governance_token);
+            self.base_tokens().insert(governance_token.clone());
+            self.base_token_added_event(&governance_token);
         }
         self.set_state_active();
     }
 
+    // egld wrapper sc. EGLD paid to the dex is wrapped into WEGLD, and WEGLD is paid out as EGLD
+    #[view(getWrapperAddress)]
+    #[storage_mapper("wrapper_address")]
+    fn wrapper_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getWegldToken)]
+    #[storage_mapper("wegld_token")]
+    fn wegld_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[only_owner]
+    #[endpoint(setEgldWrapper)]
+    fn set_egld_wrapper(&self, address: ManagedAddress, wegld_token: TokenIdentifier) {
+        require!(self.blockchain().is_smart_contract(&address), ERROR_WRONG_ADDRESS);
+        require!(wegld_token.is_valid_esdt_identifier(), ERROR_WRONG_TOKEN);
+
+        self.wrapper_address().set(&address);
+        self.wegld_token().set(&wegld_token);
+    }
+
     // fees
     #[view(getLPFee)]
     #[storage_mapper("lp_fee")]
@@ -87,6 +319,7 @@ pub trait ConfigModule {
         require!(fee + self.owner_fee().get() < MAX_PERCENT, ERROR_WRONG_FEE);
 
         self.lp_fee().set(fee);
+        self.lp_fee_changed_event(fee);
     }
 
     #[view(getOwnerFee)]
@@ -99,24 +332,153 @@ pub trait ConfigModule {
         require!(fee + self.lp_fee().get() < MAX_PERCENT, ERROR_WRONG_FEE);
 
         self.owner_fee().set(fee);
+        self.owner_fee_changed_event(fee);
+    }
+
+    // per pair overrides of the global fees
+    #[storage_mapper("pair_fees")]
+    fn pair_fees(&self, pair_id: usize) -> SingleValueMapper<PairFees>;
+
+    // returns the lp and owner fee used by the pair, falling back to the global ones
+    fn get_pair_fee_rates(&self, pair_id: usize) -> (u64, u64) {
+        if self.pair_fees(pair_id).is_empty() {
+            return (self.lp_fee().get(), self.owner_fee().get());
+        }
+
+        let fees = self.pair_fees(pair_id).get();
+        (fees.lp_fee, fees.owner_fee)
+    }
+
+    #[view(getBondingCurve)]
+    #[storage_mapper("bonding_curves")]
+    fn bonding_curves(&self, pair_id: usize) -> SingleValueMapper<BondingCurve<Self::Api>>;
+
+    #[view(getLbpSchedule)]
+    #[storage_mapper("lbp_schedule")]
+    fn lbp_schedule(&self, pair_id: usize) -> SingleValueMapper<LbpSchedule>;
+
+    // stable swap amplification
+    #[view(getPairAmpRamp)]
+    #[storage_mapper("pair_amp_ramp")]
+    fn pair_amp_ramp(&self, pair_id: usize) -> SingleValueMapper<AmpRamp>;
+
+    #[view(getPairAmp)]
+    fn get_pair_amp(&self, pair_id: usize) -> u64 {
+        require!(!self.pair_amp_ramp(pair_id).is_empty(), ERROR_NOT_STABLE_PAIR);
+
+        let ramp = self.pair_amp_ramp(pair_id).get();
+        let now = self.blockchain().get_block_timestamp();
+        if now >= ramp.future_time {
+            return ramp.future_amp;
+        }
+
+        let elapsed = now - ramp.initial_time;
+        let duration = ramp.future_time - ramp.initial_time;
+        if ramp.future_amp > ramp.initial_amp {
+            ramp.initial_amp + (ramp.future_amp - ramp.initial_amp) * elapsed / duration
+        } else {
+            ramp.initial_amp - (ramp.initial_amp - ramp.future_amp) * elapsed / duration
+        }
+    }
+
+    #[view(getPairPrecisionMultipliers)]
+    #[storage_mapper("pair_precision_multipliers")]
+    fn pair_precision_multipliers(&self, pair_id: usize) -> SingleValueMapper<PrecisionMultipliers>;
+
+    // (token multiplier, base multiplier) of a stable pair, 1 for both when never set
+    fn get_precision_multipliers(&self, pair_id: usize) -> (u64, u64) {
+        if self.pair_precision_multipliers(pair_id).is_empty() {
+            return (1, 1);
+        }
+
+        let multipliers = self.pair_precision_multipliers(pair_id).get();
+        (multipliers.token_multiplier, multipliers.base_multiplier)
+    }
+
+    #[view(getPairFees)]
+    fn get_pair_fees(&self, pair_id: usize) -> PairFees {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let (lp_fee, owner_fee) = self.get_pair_fee_rates(pair_id);
+        PairFees { lp_fee, owner_fee }
     }
 
     #[view(getCummulatedFees)]
     #[storage_mapper("cummulated_fees")]
     fn cummulated_fees(&self) -> MapMapper<TokenIdentifier, BigUint>;
 
-    #[only_owner]
-    #[endpoint(withdrawFees)]
-    fn withdraw_fees(&self) {
-        let caller = self.blockchain().get_caller();
-        let mut payments: ManagedVec<EsdtTokenPayment> = ManagedVec::new();
-        for (token, amount) in self.cummulated_fees().iter() {
-            payments.push(EsdtTokenPayment::new(token, 0, amount));
+    // user funds held by the contract outside the pairs, like the tokens of open orders or farm rewards
+    #[view(getEscrowedTokens)]
+    #[storage_mapper("escrowed_tokens")]
+    fn escrowed_tokens(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    // for every token held by the pairs or owed as fees: accounted fees and contract balance minus pair reserves.
+    // the two should match, anything else means the accounting drifted
+    #[view(getFeesReconciliation)]
+    fn get_fees_reconciliation(&self) -> MultiValueEncoded<MultiValue3<TokenIdentifier, BigUint, BigInt>> {
+        let mut tokens: ManagedVec<TokenIdentifier> = ManagedVec::new();
+        let mut reserves: ManagedVec<BigUint> = ManagedVec::new();
+        for id in 0..self.last_pair_id().get() {
+            let pair = self.pairs(id).get();
+            self.add_reserve(&mut tokens, &mut reserves, pair.token.clone(), pair.liquidity_token);
+            self.add_reserve(&mut tokens, &mut reserves, pair.base_token.clone(), pair.liquidity_base);
+            // a pair in its bonding curve phase holds the unsold and reserved tokens plus the raised base
+            if pair.state == PairState::BondingCurve {
+                let curve = self.bonding_curves(id).get();
+                let held_tokens = curve.sale_supply - curve.tokens_sold + curve.reserved_tokens;
+                self.add_reserve(&mut tokens, &mut reserves, pair.token, held_tokens);
+                self.add_reserve(&mut tokens, &mut reserves, pair.base_token, curve.base_raised);
+            }
+        }
+        for id in 0..self.last_cl_pool_id().get() {
+            let pool = self.cl_pools(id).get();
+            self.add_reserve(&mut tokens, &mut reserves, pool.token, pool.reserve_token);
+            self.add_reserve(&mut tokens, &mut reserves, pool.base_token, pool.reserve_base);
+        }
+        for (token, amount) in self.escrowed_tokens().iter() {
+            self.add_reserve(&mut tokens, &mut reserves, token, amount);
+        }
+        for token in self.cummulated_fees().keys() {
+            if !tokens.iter().any(|known| *known == token) {
+                tokens.push(token);
+                reserves.push(BigUint::zero());
+            }
+        }
+
+        let sc_address = self.blockchain().get_sc_address();
+        let mut result = MultiValueEncoded::new();
+        for (token, reserve) in tokens.iter().zip(reserves.iter()) {
+            let fees = self.cummulated_fees().get(&token).unwrap_or_default();
+            let balance = self.blockchain().get_esdt_balance(&sc_address, &token, 0);
+            let surplus = BigInt::from(balance) - BigInt::from(reserve.clone_value());
+            result.push((token.clone_value(), fees, surplus).into());
         }
-        self.cummulated_fees().clear();
-        self.send().direct_multi(&caller, &payments);
+
+        result
     }
 
+    fn add_reserve(
+        &self,
+        tokens: &mut ManagedVec<TokenIdentifier>,
+        reserves: &mut ManagedVec<BigUint>,
+        token: TokenIdentifier,
+        amount: BigUint,
+    ) {
+        match tokens.iter().position(|known| *known == token) {
+            Some(index) => {
+                let total = reserves.get(index).clone_value() + amount;
+                let _ = reserves.set(index, total);
+            }
+            None => {
+                tokens.push(token);
+                reserves.push(amount);
+            }
+        }
+    }
+
+    #[storage_mapper("reentrancy_lock")]
+    fn reentrancy_lock(&self) -> SingleValueMapper<bool>;
+
     // base tokens
     #[view(getBaseTokens)]
     #[storage_mapper("base_tokens")]
@@ -127,6 +489,30 @@ pub trait ConfigModule {
     #[storage_mapper("pairs")]
     fn pairs(&self, id: usize) -> SingleValueMapper<Pair<Self::Api>>;
 
+    // curve and weights live outside the Pair struct so pairs stored before they existed still decode.
+    // empty storage decodes as ConstantProduct
+    #[view(getPairCurve)]
+    #[storage_mapper("pair_curve")]
+    fn pair_curve(&self, pair_id: usize) -> SingleValueMapper<CurveType>;
+
+    #[storage_mapper("pair_weights")]
+    fn pair_weights(&self, pair_id: usize) -> SingleValueMapper<PairWeights>;
+
+    // (weight token, weight base) stored for the pair, 50/50 when never set
+    fn get_stored_weights(&self, pair_id: usize) -> (u64, u64) {
+        if self.pair_weights(pair_id).is_empty() {
+            return (MAX_PERCENT / 2, MAX_PERCENT / 2);
+        }
+
+        let weights = self.pair_weights(pair_id).get();
+        (weights.weight_token, weights.weight_base)
+    }
+
+    // empty storage decodes as Pending. pairs created before roles were tracked are synced on upgrade
+    #[view(getLpRolesState)]
+    #[storage_mapper("lp_roles_state")]
+    fn lp_roles_state(&self, pair_id: usize) -> SingleValueMapper<LpRolesState>;
+
     #[view(getLastPairId)]
     #[storage_mapper("last_pair_id")]
     fn last_pair_id(&self) -> SingleValueMapper<usize>;
@@ -144,34 +530,100 @@ pub trait ConfigModule {
 
     #[view(getPairByTickers)]
     fn get_pair_by_tickers(&self, base_token: &TokenIdentifier, token: &TokenIdentifier) -> Option<Pair<Self::Api>> {
-        let last_pair_id = self.last_pair_id().get();
-        for id in 0..last_pair_id {
-            let pair = self.pairs(id).get();
-            if &pair.base_token == base_token && &pair.token == token {
-                return Some(pair);
-            }
-            if &pair.token == base_token && &pair.base_token == token {
-                return Some(pair);
-            }
-        }
-
-        None
+        self.token_pairs(base_token).get(token).map(|id| self.pairs(id).get())
     }
 
     #[view(getPairByLpToken)]
     fn get_pair_by_lp_token(&self, lp_token: &TokenIdentifier) -> Option<Pair<Self::Api>> {
-        let last_pair_id = self.last_pair_id().get();
-        for id in 0..last_pair_id {
-            let pair = self.pairs(id).get();
-            if &pair.lp_token == lp_token {
-                return Some(pair);
+        self.lp_token_pairs().get(lp_token).map(|id| self.pairs(id).get())
+    }
+
+    // concentrated liquidity
+    #[view(getClPositionToken)]
+    #[storage_mapper("cl_position_token")]
+    fn cl_position_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getClPool)]
+    #[storage_mapper("cl_pools")]
+    fn cl_pools(&self, id: usize) -> SingleValueMapper<ClPool<Self::Api>>;
+
+    #[view(getLastClPoolId)]
+    #[storage_mapper("last_cl_pool_id")]
+    fn last_cl_pool_id(&self) -> SingleValueMapper<usize>;
+
+    #[view(getClTick)]
+    #[storage_mapper("cl_ticks")]
+    fn cl_ticks(&self, pool_id: usize, tick: i64) -> SingleValueMapper<ClTick<Self::Api>>;
+
+    // initialized ticks of a pool, one bit per tick spacing, CL_TICK_WORD_BITS of them per word
+    #[view(getClTickBitmap)]
+    #[storage_mapper("cl_tick_bitmap")]
+    fn cl_tick_bitmap(&self, pool_id: usize, word: i64) -> SingleValueMapper<u64>;
+
+    #[view(getClMinPositionLiquidity)]
+    #[storage_mapper("cl_min_position_liquidity")]
+    fn cl_min_position_liquidity(&self, pool_id: usize) -> SingleValueMapper<BigUint>;
+
+    #[view(getClPools)]
+    fn get_cl_pools(&self) -> ManagedVec<ClPool<Self::Api>> {
+        let mut pools = ManagedVec::new();
+        for id in 0..self.last_cl_pool_id().get() {
+            pools.push(self.cl_pools(id).get());
+        }
+
+        pools
+    }
+
+    // pair indexes
+    // other token -> pair id, kept for both tokens of a pair so lookups don't care which one is the base
+    #[storage_mapper("token_pairs")]
+    fn token_pairs(&self, token: &TokenIdentifier) -> MapMapper<TokenIdentifier, usize>;
+
+    #[storage_mapper("lp_token_pairs")]
+    fn lp_token_pairs(&self) -> MapMapper<TokenIdentifier, usize>;
+
+    #[view(getBaseTokenPairs)]
+    #[storage_mapper("base_token_pairs")]
+    fn base_token_pairs(&self, base_token: &TokenIdentifier) -> UnorderedSetMapper<usize>;
+
+    fn index_pair(&self, pair: &Pair<Self::Api>) {
+        self.token_pairs(&pair.token).insert(pair.base_token.clone(), pair.id);
+        self.token_pairs(&pair.base_token).insert(pair.token.clone(), pair.id);
+        self.lp_token_pairs().insert(pair.lp_token.clone(), pair.id);
+        self.base_token_pairs(&pair.base_token).insert(pair.id);
+    }
+
+    // backfills the indexes for pairs created before they existed. safe to run more than once
+    fn index_existing_pairs(&self) {
+        for id in 0..self.last_pair_id().get() {
+            if self.pairs(id).is_empty() {
+                continue;
             }
+
+            self.index_pair(&self.pairs(id).get());
         }
+    }
+
+    // marks as Granted the pending pairs whose LP token the contract can already mint and burn, like the ones
+    // created before roles were tracked
+    fn sync_lp_roles_states(&self) {
+        for id in 0..self.last_pair_id().get() {
+            if self.pairs(id).is_empty() || self.lp_roles_state(id).get() == LpRolesState::Granted {
+                continue;
+            }
 
-        None
+            let roles = self.blockchain().get_esdt_local_roles(&self.pairs(id).get().lp_token);
+            if roles.has_role(&EsdtLocalRole::Mint) && roles.has_role(&EsdtLocalRole::Burn) {
+                self.lp_roles_state(id).set(LpRolesState::Granted);
+                self.lp_roles_granted_event(id);
+            }
+        }
     }
 
     // proxies
     #[proxy]
     fn launchpad_contract_proxy(&self) -> launchpad_proxy::Proxy<Self::Api>;
+
+    #[proxy]
+    fn wrapper_contract_proxy(&self) -> wrapper_proxy::Proxy<Self::Api>;
 }
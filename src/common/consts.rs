@@ -3,3 +3,30 @@ pub const LP_TOKEN_PREFIX: &[u8] = b"TFN";
 pub const LP_TOKEN_SUFFIX: &[u8] = b"LP";
 pub const MAX_PERCENT: u64 = 10000;
 pub const TOKEN_ISSUE_COST: u64 = 50_000_000_000_000_000;
+pub const MAX_ROUTE_HOPS: usize = 4;
+pub const PRICE_PRECISION: u64 = 1_000_000_000_000_000_000;
+pub const PRICE_OBSERVATIONS_CAPACITY: usize = 64;
+pub const PRICE_OBSERVATION_PERIOD: u64 = 60;
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+pub const EGLD_000000_TOKEN_ID: &[u8] = b"EGLD-000000";
+pub const MAX_AMP: u64 = 1_000_000;
+pub const MIN_AMP_RAMP_TIME: u64 = 86_400;
+pub const MAX_AMP_CHANGE: u64 = 10;
+pub const STABLE_MAX_ITERATIONS: usize = 255;
+pub const MAX_STABLE_DECIMALS: u32 = 18;
+pub const FIXED_POINT_ONE: u64 = 1_000_000_000_000_000_000;
+pub const MIN_WEIGHT: u64 = 100;
+pub const WEIGHTED_MAX_IN_RATIO: u64 = 5_000;
+pub const WEIGHTED_MAX_OUT_RATIO: u64 = 3_333;
+pub const POW_MAX_ITERATIONS: usize = 100;
+pub const POW_PRECISION: u64 = 100_000_000;
+pub const E_FIXED_POINT: u64 = 2_718_281_828_459_045_235;
+pub const MAX_EXP_EXPONENT: u64 = 100;
+pub const CL_MAX_TICK: i64 = 887_272;
+pub const CL_MAX_TICK_SPACING: i64 = 10_000;
+pub const CL_TICK_WORD_BITS: i64 = 64;
+pub const CL_LN_TICK_BASE: u64 = 99_995_000_333_308;
+pub const CL_POSITION_TOKEN_NAME: &[u8] = b"TFNConcentratedLP";
+pub const CL_POSITION_TOKEN_TICKER: &[u8] = b"TFNCLP";
+pub const MAX_KEEPER_FEE: u64 = 100;
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
@@ -18,3 +18,62 @@ pub static ERROR_NO_BASE_TOKENS: &[u8] = b"no base tokens";
 pub static ERROR_ZERO_AMOUNT: &[u8] = b"zero amount";
 pub static ERROR_NOT_SUBSCRIBER: &[u8] = b"only the platform subscriber can call this function";
 pub static ERROR_WRONG_ISSUE_COST: &[u8] = b"wrong issue cost";
+pub static ERROR_INVALID_PATH: &[u8] = b"invalid swap path";
+pub static ERROR_WRONG_MAX_HOPS: &[u8] = b"wrong max hops";
+pub static ERROR_ROUTE_NOT_FOUND: &[u8] = b"route not found";
+pub static ERROR_WRONG_TWAP_WINDOW: &[u8] = b"wrong twap window";
+pub static ERROR_NOT_ENOUGH_OBSERVATIONS: &[u8] = b"not enough price observations";
+pub static ERROR_LP_ROLES_NOT_GRANTED: &[u8] = b"lp token roles not granted";
+pub static ERROR_LP_ROLES_ALREADY_GRANTED: &[u8] = b"lp token roles already granted";
+pub static ERROR_DEADLINE_PASSED: &[u8] = b"deadline passed";
+pub static ERROR_INSUFFICIENT_TOKEN_AMOUNT: &[u8] = b"insufficient token amount";
+pub static ERROR_INSUFFICIENT_BASE_AMOUNT: &[u8] = b"insufficient base amount";
+pub static ERROR_INSUFFICIENT_LP_AMOUNT: &[u8] = b"insufficient lp amount";
+pub static ERROR_INSUFFICIENT_INITIAL_LIQUIDITY: &[u8] = b"insufficient initial liquidity";
+pub static ERROR_PAIR_FEES_NOT_SET: &[u8] = b"pair fees not set";
+pub static ERROR_REENTRANCY: &[u8] = b"reentrant call";
+pub static ERROR_FLASH_SWAP_NOT_REPAID: &[u8] = b"flash swap not repaid";
+pub static ERROR_FLASH_LOAN_NOT_REPAID: &[u8] = b"flash loan not repaid";
+pub static ERROR_EGLD_WRAPPER_NOT_SET: &[u8] = b"egld wrapper not set";
+pub static ERROR_WRONG_ADDRESS: &[u8] = b"wrong address";
+pub static ERROR_WRONG_TOKEN: &[u8] = b"wrong token";
+pub static ERROR_WRONG_AMP: &[u8] = b"wrong amplification";
+pub static ERROR_WRONG_RAMP_TIME: &[u8] = b"wrong ramp time";
+pub static ERROR_PAIR_NOT_EMPTY: &[u8] = b"pair not empty";
+pub static ERROR_NOT_STABLE_PAIR: &[u8] = b"not a stable pair";
+pub static ERROR_STABLE_MATH_NOT_CONVERGED: &[u8] = b"stable math did not converge";
+pub static ERROR_WRONG_DECIMALS: &[u8] = b"wrong decimals";
+pub static ERROR_WRONG_WEIGHT: &[u8] = b"wrong weight";
+pub static ERROR_MAX_RATIO_EXCEEDED: &[u8] = b"amount exceeds max ratio";
+pub static ERROR_WRONG_CURVE: &[u8] = b"wrong curve type";
+pub static ERROR_WRONG_LBP_SCHEDULE: &[u8] = b"wrong lbp schedule";
+pub static ERROR_LBP_IN_PROGRESS: &[u8] = b"lbp in progress";
+pub static ERROR_EXPONENT_TOO_LARGE: &[u8] = b"exponent too large";
+pub static ERROR_PAIR_IN_BONDING_CURVE: &[u8] = b"pair in bonding curve";
+pub static ERROR_PAIR_NOT_IN_BONDING_CURVE: &[u8] = b"pair not in bonding curve";
+pub static ERROR_WRONG_BONDING_CURVE: &[u8] = b"wrong bonding curve";
+pub static ERROR_BONDING_CURVE_SOLD_OUT: &[u8] = b"not enough tokens left";
+pub static ERROR_CL_POOL_NOT_FOUND: &[u8] = b"concentrated pool not found";
+pub static ERROR_CL_POSITION_TOKEN_NOT_SET: &[u8] = b"position token not set";
+pub static ERROR_CL_POSITION_TOKEN_ALREADY_SET: &[u8] = b"position token already set";
+pub static ERROR_WRONG_TICK: &[u8] = b"wrong tick";
+pub static ERROR_WRONG_PRICE: &[u8] = b"wrong price";
+pub static ERROR_INSUFFICIENT_LIQUIDITY: &[u8] = b"insufficient liquidity";
+pub static ERROR_ORDER_NOT_FOUND: &[u8] = b"order not found";
+pub static ERROR_NOT_ORDER_OWNER: &[u8] = b"only the order owner can call this function";
+pub static ERROR_DCA_NOT_FOUND: &[u8] = b"dca schedule not found";
+pub static ERROR_NOT_DCA_OWNER: &[u8] = b"only the dca schedule owner can call this function";
+pub static ERROR_WRONG_DCA_SCHEDULE: &[u8] = b"wrong dca schedule";
+pub static ERROR_DCA_FINISHED: &[u8] = b"dca schedule finished";
+pub static ERROR_DCA_TOO_EARLY: &[u8] = b"dca interval not elapsed";
+pub static ERROR_DCA_PRICE_TOO_LOW: &[u8] = b"price below the dca minimum";
+pub static ERROR_WRONG_ORDER: &[u8] = b"wrong order parameters";
+pub static ERROR_ORDER_EXPIRED: &[u8] = b"order expired";
+pub static ERROR_ORDER_NOT_TRIGGERED: &[u8] = b"order not triggered";
+pub static ERROR_MAX_SLIPPAGE_EXCEEDED: &[u8] = b"max slippage exceeded";
+pub static ERROR_FARM_NOT_FOUND: &[u8] = b"farm not found";
+pub static ERROR_WRONG_FARM_SCHEDULE: &[u8] = b"wrong farm schedule";
+pub static ERROR_FARM_IN_PROGRESS: &[u8] = b"farm in progress";
+pub static ERROR_WRONG_REWARD_TOKEN: &[u8] = b"wrong reward token";
+pub static ERROR_NOT_ENOUGH_STAKED: &[u8] = b"not enough staked";
+pub static ERROR_NO_REWARDS: &[u8] = b"no rewards";
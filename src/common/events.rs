@@ -0,0 +1,466 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::common::config::{AmpRamp, BondingCurve, ClPool, ClPosition, ConditionalOrder, CurveType, DcaSchedule, Farm, LbpSchedule, LimitOrder, Pair, PairFees, PairState, PrecisionMultipliers};
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct SwapEvent<M: ManagedTypeApi> {
+    pub amount_in: BigUint<M>,
+    pub amount_out: BigUint<M>,
+    pub fee_token: TokenIdentifier<M>,
+    pub lp_fee: BigUint<M>,
+    pub owner_fee: BigUint<M>,
+    pub liquidity_token: BigUint<M>,
+    pub liquidity_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct LiquidityEvent<M: ManagedTypeApi> {
+    pub token_amount: BigUint<M>,
+    pub base_amount: BigUint<M>,
+    pub lp_amount: BigUint<M>,
+    pub lp_supply: BigUint<M>,
+    pub liquidity_token: BigUint<M>,
+    pub liquidity_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct FlashSwapEvent<M: ManagedTypeApi> {
+    pub amount_out: BigUint<M>,
+    pub token_repaid: BigUint<M>,
+    pub base_repaid: BigUint<M>,
+    pub liquidity_token: BigUint<M>,
+    pub liquidity_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct FlashLoanEvent<M: ManagedTypeApi> {
+    pub amount: BigUint<M>,
+    pub lp_fee: BigUint<M>,
+    pub owner_fee: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct BondingCurveTradeEvent<M: ManagedTypeApi> {
+    pub base_amount: BigUint<M>,
+    pub token_amount: BigUint<M>,
+    pub lp_fee: BigUint<M>,
+    pub owner_fee: BigUint<M>,
+    pub tokens_sold: BigUint<M>,
+    pub base_raised: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClSwapEvent<M: ManagedTypeApi> {
+    pub amount_in: BigUint<M>,
+    pub amount_out: BigUint<M>,
+    pub lp_fee: BigUint<M>,
+    pub owner_fee: BigUint<M>,
+    pub sqrt_price: BigUint<M>,
+    pub current_tick: i64,
+    pub liquidity: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClPositionEvent<M: ManagedTypeApi> {
+    pub nonce: u64,
+    pub position: ClPosition<M>,
+    pub token_amount: BigUint<M>,
+    pub base_amount: BigUint<M>,
+    pub fees_token: BigUint<M>,
+    pub fees_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct OrderExecutedEvent<M: ManagedTypeApi> {
+    pub amount_out: BigUint<M>,
+    pub keeper_fee: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct DcaExecutedEvent<M: ManagedTypeApi> {
+    pub amount_in: BigUint<M>,
+    pub amount_out: BigUint<M>,
+    pub remaining: BigUint<M>,
+    pub accumulated: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait EventsModule {
+    #[event("swap")]
+    fn swap_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token_in: &TokenIdentifier,
+        #[indexed] token_out: &TokenIdentifier,
+        data: &SwapEvent<Self::Api>,
+    );
+
+    #[event("flashSwap")]
+    fn flash_swap_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token_out: &TokenIdentifier,
+        data: &FlashSwapEvent<Self::Api>,
+    );
+
+    #[event("flashLoan")]
+    fn flash_loan_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token: &TokenIdentifier,
+        data: &FlashLoanEvent<Self::Api>,
+    );
+
+    #[event("addLiquidity")]
+    fn add_liquidity_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &LiquidityEvent<Self::Api>,
+    );
+
+    #[event("removeLiquidity")]
+    fn remove_liquidity_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &LiquidityEvent<Self::Api>,
+    );
+
+    #[event("orderPlaced")]
+    fn order_placed_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        data: &LimitOrder<Self::Api>,
+    );
+
+    #[event("orderCancelled")]
+    fn order_cancelled_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] owner: &ManagedAddress,
+    );
+
+    #[event("orderExecuted")]
+    fn order_executed_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] keeper: &ManagedAddress,
+        data: &OrderExecutedEvent<Self::Api>,
+    );
+
+    #[event("conditionalOrderPlaced")]
+    fn conditional_order_placed_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        data: &ConditionalOrder<Self::Api>,
+    );
+
+    #[event("conditionalOrderCancelled")]
+    fn conditional_order_cancelled_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] owner: &ManagedAddress,
+    );
+
+    #[event("conditionalOrderExecuted")]
+    fn conditional_order_executed_event(
+        &self,
+        #[indexed] order_id: u64,
+        #[indexed] keeper: &ManagedAddress,
+        data: &OrderExecutedEvent<Self::Api>,
+    );
+
+    #[event("dcaCreated")]
+    fn dca_created_event(
+        &self,
+        #[indexed] schedule_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        data: &DcaSchedule<Self::Api>,
+    );
+
+    #[event("dcaExecuted")]
+    fn dca_executed_event(
+        &self,
+        #[indexed] schedule_id: u64,
+        #[indexed] caller: &ManagedAddress,
+        data: &DcaExecutedEvent<Self::Api>,
+    );
+
+    #[event("dcaClaimed")]
+    fn dca_claimed_event(
+        &self,
+        #[indexed] schedule_id: u64,
+        #[indexed] owner: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("dcaCancelled")]
+    fn dca_cancelled_event(
+        &self,
+        #[indexed] schedule_id: u64,
+        #[indexed] owner: &ManagedAddress,
+    );
+
+    #[event("clSwap")]
+    fn cl_swap_event(
+        &self,
+        #[indexed] pool_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token_in: &TokenIdentifier,
+        #[indexed] token_out: &TokenIdentifier,
+        data: &ClSwapEvent<Self::Api>,
+    );
+
+    #[event("clPoolCreated")]
+    fn cl_pool_created_event(
+        &self,
+        #[indexed] pool_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &ClPool<Self::Api>,
+    );
+
+    #[event("clMinPositionLiquidityChanged")]
+    fn cl_min_position_liquidity_changed_event(
+        &self,
+        #[indexed] pool_id: usize,
+        min_liquidity: &BigUint,
+    );
+
+    #[event("clPositionCreated")]
+    fn cl_position_created_event(
+        &self,
+        #[indexed] pool_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &ClPositionEvent<Self::Api>,
+    );
+
+    #[event("clPositionRemoved")]
+    fn cl_position_removed_event(
+        &self,
+        #[indexed] pool_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &ClPositionEvent<Self::Api>,
+    );
+
+    #[event("clFeesCollected")]
+    fn cl_fees_collected_event(
+        &self,
+        #[indexed] pool_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &ClPositionEvent<Self::Api>,
+    );
+
+    #[event("clPositionTokenIssued")]
+    fn cl_position_token_issued_event(
+        &self,
+        token: &TokenIdentifier,
+    );
+
+    #[event("pairCreated")]
+    fn pair_created_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &Pair<Self::Api>,
+    );
+
+    #[event("pairStateChanged")]
+    fn pair_state_changed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        state: PairState,
+    );
+
+    #[event("pairCurveChanged")]
+    fn pair_curve_changed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        curve: CurveType,
+    );
+
+    #[event("pairWeightsChanged")]
+    fn pair_weights_changed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] weight_token: u64,
+        weight_base: u64,
+    );
+
+    #[event("bondingCurveStarted")]
+    fn bonding_curve_started_event(
+        &self,
+        #[indexed] pair_id: usize,
+        data: &BondingCurve<Self::Api>,
+    );
+
+    #[event("bondingCurveBuy")]
+    fn bonding_curve_buy_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &BondingCurveTradeEvent<Self::Api>,
+    );
+
+    #[event("bondingCurveSell")]
+    fn bonding_curve_sell_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        data: &BondingCurveTradeEvent<Self::Api>,
+    );
+
+    #[event("bondingCurveGraduated")]
+    fn bonding_curve_graduated_event(
+        &self,
+        #[indexed] pair_id: usize,
+        data: &LiquidityEvent<Self::Api>,
+    );
+
+    #[event("lbpStarted")]
+    fn lbp_started_event(
+        &self,
+        #[indexed] pair_id: usize,
+        schedule: &LbpSchedule,
+    );
+
+    #[event("lbpEnded")]
+    fn lbp_ended_event(
+        &self,
+        #[indexed] pair_id: usize,
+        curve: CurveType,
+    );
+
+    #[event("pairAmpRampChanged")]
+    fn pair_amp_ramp_changed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        ramp: &AmpRamp,
+    );
+
+    #[event("pairPrecisionMultipliersChanged")]
+    fn pair_precision_multipliers_changed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        multipliers: &PrecisionMultipliers,
+    );
+
+    #[event("lpRolesGranted")]
+    fn lp_roles_granted_event(
+        &self,
+        #[indexed] pair_id: usize,
+    );
+
+    #[event("baseTokenAdded")]
+    fn base_token_added_event(
+        &self,
+        #[indexed] token: &TokenIdentifier,
+    );
+
+    #[event("baseTokenRemoved")]
+    fn base_token_removed_event(
+        &self,
+        #[indexed] token: &TokenIdentifier,
+    );
+
+    #[event("lpFeeChanged")]
+    fn lp_fee_changed_event(
+        &self,
+        fee: u64,
+    );
+
+    #[event("ownerFeeChanged")]
+    fn owner_fee_changed_event(
+        &self,
+        fee: u64,
+    );
+
+    #[event("pairFeesChanged")]
+    fn pair_fees_changed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        fees: &PairFees,
+    );
+
+    #[event("flashLoanFeeChanged")]
+    fn flash_loan_fee_changed_event(
+        &self,
+        fee: u64,
+    );
+
+    #[event("orderKeeperFeeChanged")]
+    fn order_keeper_fee_changed_event(
+        &self,
+        fee: u64,
+    );
+
+    #[event("farmFunded")]
+    fn farm_funded_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        farm: &Farm<Self::Api>,
+    );
+
+    #[event("lpStaked")]
+    fn lp_staked_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("lpUnstaked")]
+    fn lp_unstaked_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("farmRewardsClaimed")]
+    fn farm_rewards_claimed_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("farmRewardsCompounded")]
+    fn farm_rewards_compounded_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] reward_amount: &BigUint,
+        lp_amount: &BigUint,
+    );
+
+    #[event("farmLeftoverWithdrawn")]
+    fn farm_leftover_withdrawn_event(
+        &self,
+        #[indexed] pair_id: usize,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+
+    #[event("feesWithdrawn")]
+    fn fees_withdrawn_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        payments: &ManagedVec<EsdtTokenPayment<Self::Api>>,
+    );
+}
@@ -0,0 +1,624 @@
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
+
+multiversx_sc::imports!();
+
+// concentrated liquidity pools, living next to the fungible LP pairs under their own ids. positions are NFTs
+// of the cl_position_token collection carrying their range, liquidity and fee growth snapshots as attributes.
+// the price of tick t is 1.0001 ^ t base per token
+#[multiversx_sc::module]
+pub trait ConcentratedLiquidityModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
+{
+    // initial_price is in base per token, scaled by PRICE_PRECISION
+    #[endpoint(createClPool)]
+    fn create_cl_pool(&self, base_token: TokenIdentifier, token: TokenIdentifier, tick_spacing: i64, initial_price: BigUint) {
+        self.only_owner_or_launchpad();
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(self.base_tokens().contains(&base_token) && base_token != token, ERROR_WRONG_BASE_TOKEN);
+        require!(tick_spacing > 0 && tick_spacing <= CL_MAX_TICK_SPACING, ERROR_WRONG_TICK);
+
+        let sqrt_price = (initial_price * PRICE_PRECISION).sqrt();
+        require!(
+            sqrt_price > self.get_cl_sqrt_price_at_tick(-CL_MAX_TICK) && sqrt_price < self.get_cl_sqrt_price_at_tick(CL_MAX_TICK),
+            ERROR_WRONG_PRICE
+        );
+
+        let id = self.last_cl_pool_id().get();
+        let pool = ClPool {
+            id,
+            token,
+            base_token,
+            tick_spacing,
+            current_tick: self.get_cl_tick_at_sqrt_price(&sqrt_price),
+            sqrt_price,
+            liquidity: BigUint::zero(),
+            fee_growth_token: BigUint::zero(),
+            fee_growth_base: BigUint::zero(),
+            reserve_token: BigUint::zero(),
+            reserve_base: BigUint::zero(),
+        };
+        self.last_cl_pool_id().set(id + 1);
+        self.cl_pools(id).set(&pool);
+        self.cl_pool_created_event(id, &self.blockchain().get_caller(), &pool);
+    }
+
+    // positions below this liquidity are refused, so that every tick a swap has to cross is worth setting up
+    #[only_owner]
+    #[endpoint(setClMinPositionLiquidity)]
+    fn set_cl_min_position_liquidity(&self, pool_id: usize, min_liquidity: BigUint) {
+        self.get_cl_pool(pool_id);
+
+        self.cl_min_position_liquidity(pool_id).set(&min_liquidity);
+        self.cl_min_position_liquidity_changed_event(pool_id, &min_liquidity);
+    }
+
+    // mints a position over [tick_lower, tick_upper) from the pool token and/or base token sent. only the
+    // amounts matching the current price are used, the rest is refunded
+    #[payable("*")]
+    #[endpoint(addClPosition)]
+    fn add_cl_position(&self, pool_id: usize, tick_lower: i64, tick_upper: i64, min_liquidity: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.cl_position_token().is_empty(), ERROR_CL_POSITION_TOKEN_NOT_SET);
+        self.require_not_locked();
+
+        let mut pool = self.get_cl_pool(pool_id);
+        require!(
+            tick_lower < tick_upper &&
+            tick_lower >= -CL_MAX_TICK &&
+            tick_upper <= CL_MAX_TICK &&
+            tick_lower % pool.tick_spacing == 0 &&
+            tick_upper % pool.tick_spacing == 0,
+            ERROR_WRONG_TICK
+        );
+
+        let mut token_amount = BigUint::zero();
+        let mut base_amount = BigUint::zero();
+        for payment in self.get_multi_payments().iter() {
+            if payment.token_identifier == pool.token && token_amount == 0 {
+                token_amount = payment.amount.clone();
+            } else if payment.token_identifier == pool.base_token && base_amount == 0 {
+                base_amount = payment.amount.clone();
+            } else {
+                sc_panic!(ERROR_WRONG_PAYMENT);
+            }
+        }
+
+        let sqrt_lower = self.get_cl_sqrt_price_at_tick(tick_lower);
+        let sqrt_upper = self.get_cl_sqrt_price_at_tick(tick_upper);
+        let liquidity = self.get_cl_liquidity_for_amounts(&pool.sqrt_price, &sqrt_lower, &sqrt_upper, &token_amount, &base_amount);
+        require!(
+            liquidity > 0 && liquidity >= min_liquidity && liquidity >= self.cl_min_position_liquidity(pool_id).get(),
+            ERROR_INSUFFICIENT_LIQUIDITY
+        );
+
+        let (token_used, base_used) = self.get_cl_amounts(&pool.sqrt_price, &sqrt_lower, &sqrt_upper, &liquidity, true);
+        require!(token_used <= token_amount && base_used <= base_amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
+
+        self.update_cl_tick(&pool, tick_lower, &liquidity, true, false);
+        self.update_cl_tick(&pool, tick_upper, &liquidity, true, true);
+        if tick_lower <= pool.current_tick && pool.current_tick < tick_upper {
+            pool.liquidity += &liquidity;
+        }
+        pool.reserve_token += &token_used;
+        pool.reserve_base += &base_used;
+        self.cl_pools(pool_id).set(&pool);
+
+        let (fee_growth_inside_token, fee_growth_inside_base) = self.get_cl_fee_growth_inside(&pool, tick_lower, tick_upper);
+        let position = ClPosition {
+            pool_id,
+            tick_lower,
+            tick_upper,
+            liquidity,
+            fee_growth_inside_token,
+            fee_growth_inside_base,
+        };
+        let caller = self.blockchain().get_caller();
+        self.send_payout(&caller, &pool.token, &(token_amount - &token_used));
+        self.send_payout(&caller, &pool.base_token, &(base_amount - &base_used));
+        let nonce = self.mint_cl_position(&caller, &position);
+
+        self.cl_position_created_event(pool_id, &caller, &ClPositionEvent {
+            nonce,
+            position,
+            token_amount: token_used,
+            base_amount: base_used,
+            fees_token: BigUint::zero(),
+            fees_base: BigUint::zero(),
+        });
+    }
+
+    // burns the position NFT sent and pays out its liquidity together with the uncollected fees
+    #[payable("*")]
+    #[endpoint(removeClPosition)]
+    fn remove_cl_position(&self, min_token_amount: BigUint, min_base_amount: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+
+        let (nonce, position) = self.get_cl_position_payment();
+        let mut pool = self.get_cl_pool(position.pool_id);
+        let (fees_token, fees_base, _, _) = self.get_cl_fees_owed(&pool, &position);
+        let sqrt_lower = self.get_cl_sqrt_price_at_tick(position.tick_lower);
+        let sqrt_upper = self.get_cl_sqrt_price_at_tick(position.tick_upper);
+        let (token_amount, base_amount) =
+            self.get_cl_amounts(&pool.sqrt_price, &sqrt_lower, &sqrt_upper, &position.liquidity, false);
+        require!(token_amount >= min_token_amount, ERROR_INSUFFICIENT_TOKEN_AMOUNT);
+        require!(base_amount >= min_base_amount, ERROR_INSUFFICIENT_BASE_AMOUNT);
+
+        self.update_cl_tick(&pool, position.tick_lower, &position.liquidity, false, false);
+        self.update_cl_tick(&pool, position.tick_upper, &position.liquidity, false, true);
+        if position.tick_lower <= pool.current_tick && pool.current_tick < position.tick_upper {
+            pool.liquidity -= &position.liquidity;
+        }
+        let token_out = &token_amount + &fees_token;
+        let base_out = &base_amount + &fees_base;
+        pool.reserve_token -= &token_out;
+        pool.reserve_base -= &base_out;
+        self.cl_pools(pool.id).set(&pool);
+        self.send().esdt_local_burn(&self.cl_position_token().get(), nonce, &BigUint::from(1u64));
+
+        let caller = self.blockchain().get_caller();
+        self.send_payout(&caller, &pool.token, &token_out);
+        self.send_payout(&caller, &pool.base_token, &base_out);
+        self.cl_position_removed_event(pool.id, &caller, &ClPositionEvent {
+            nonce,
+            position,
+            token_amount,
+            base_amount,
+            fees_token,
+            fees_base,
+        });
+    }
+
+    // pays out the fees earned by the position NFT sent and gives back a new NFT with fresh snapshots
+    #[payable("*")]
+    #[endpoint(collectClFees)]
+    fn collect_cl_fees(&self) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+
+        let (nonce, mut position) = self.get_cl_position_payment();
+        let mut pool = self.get_cl_pool(position.pool_id);
+        let (fees_token, fees_base, fee_growth_inside_token, fee_growth_inside_base) = self.get_cl_fees_owed(&pool, &position);
+        position.fee_growth_inside_token = fee_growth_inside_token;
+        position.fee_growth_inside_base = fee_growth_inside_base;
+        pool.reserve_token -= &fees_token;
+        pool.reserve_base -= &fees_base;
+        self.cl_pools(pool.id).set(&pool);
+        self.send().esdt_local_burn(&self.cl_position_token().get(), nonce, &BigUint::from(1u64));
+
+        let caller = self.blockchain().get_caller();
+        let new_nonce = self.mint_cl_position(&caller, &position);
+        self.send_payout(&caller, &pool.token, &fees_token);
+        self.send_payout(&caller, &pool.base_token, &fees_base);
+        self.cl_fees_collected_event(pool.id, &caller, &ClPositionEvent {
+            nonce: new_nonce,
+            position,
+            token_amount: BigUint::zero(),
+            base_amount: BigUint::zero(),
+            fees_token,
+            fees_base,
+        });
+    }
+
+    // fixed input swap against a pool. fees are charged in the input token, always at the global rates: pair fee
+    // overrides are keyed by pair id and don't apply to concentrated pools
+    #[payable("*")]
+    #[endpoint(swapCl)]
+    fn swap_cl(&self, pool_id: usize, min_amount_out: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+
+        let payment = self.get_single_payment();
+        let mut pool = self.get_cl_pool(pool_id);
+        let token_in_is_token = self.is_cl_token_in(&pool, &payment.token_identifier);
+        let (lp_fee, owner_fee) = self.get_cl_fee_amounts(&payment.amount);
+        let amount_in = &payment.amount - &lp_fee - &owner_fee;
+        let amount_out = self.cl_swap(&mut pool, token_in_is_token, &amount_in, &lp_fee, true);
+        require!(amount_out > 0 && amount_out >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+        let token_out = if token_in_is_token {
+            pool.reserve_token += &amount_in + &lp_fee;
+            pool.reserve_base -= &amount_out;
+            pool.base_token.clone()
+        } else {
+            pool.reserve_base += &amount_in + &lp_fee;
+            pool.reserve_token -= &amount_out;
+            pool.token.clone()
+        };
+        self.add_cummulated_fee(&payment.token_identifier, &owner_fee);
+        self.cl_pools(pool_id).set(&pool);
+
+        let caller = self.blockchain().get_caller();
+        self.send_payout(&caller, &token_out, &amount_out);
+        self.cl_swap_event(pool_id, &caller, &payment.token_identifier, &token_out, &ClSwapEvent {
+            amount_in: payment.amount,
+            amount_out,
+            lp_fee,
+            owner_fee,
+            sqrt_price: pool.sqrt_price,
+            current_tick: pool.current_tick,
+            liquidity: pool.liquidity,
+        });
+    }
+
+    // walks amount_in, fees already taken, through the initialized ticks of the pool one bitmap word at most at
+    // a time. lp_fee is spread over the liquidity of every range crossed. tick storage is only written when
+    // commit is set, so quotes can run the same path
+    fn cl_swap(
+        &self,
+        pool: &mut ClPool<Self::Api>,
+        token_in_is_token: bool,
+        amount_in: &BigUint,
+        lp_fee: &BigUint,
+        commit: bool,
+    ) -> BigUint {
+        let one = BigUint::from(FIXED_POINT_ONE);
+        let mut remaining = amount_in.clone();
+        let mut fee_left = lp_fee.clone();
+        let mut amount_out = BigUint::zero();
+        while remaining > 0 {
+            // selling the token moves the price down, buying it moves the price up
+            let (next_tick, initialized) = self.get_next_cl_tick(pool, token_in_is_token);
+            let target = self.get_cl_sqrt_price_at_tick(next_tick);
+
+            let mut reached = true;
+            if pool.liquidity > 0 {
+                let max_in = if token_in_is_token {
+                    self.get_cl_token_delta(&target, &pool.sqrt_price, &pool.liquidity, true)
+                } else {
+                    self.get_cl_base_delta(&pool.sqrt_price, &target, &pool.liquidity, true)
+                };
+                let (step_in, new_sqrt_price) = if remaining >= max_in {
+                    (max_in, target.clone())
+                } else if token_in_is_token {
+                    reached = false;
+                    let numerator = &pool.liquidity * &pool.sqrt_price * &one;
+                    let denominator = &pool.liquidity * &one + &remaining * &pool.sqrt_price;
+                    (remaining.clone(), (numerator + &denominator - 1u64) / denominator)
+                } else {
+                    reached = false;
+                    (remaining.clone(), &pool.sqrt_price + &(&remaining * &one / &pool.liquidity))
+                };
+                amount_out += if token_in_is_token {
+                    self.get_cl_base_delta(&new_sqrt_price, &pool.sqrt_price, &pool.liquidity, false)
+                } else {
+                    self.get_cl_token_delta(&pool.sqrt_price, &new_sqrt_price, &pool.liquidity, false)
+                };
+                pool.sqrt_price = new_sqrt_price;
+
+                let step_fee = if step_in == remaining {
+                    fee_left.clone()
+                } else {
+                    lp_fee * &step_in / amount_in
+                };
+                let fee_growth = &step_fee * &one / &pool.liquidity;
+                if token_in_is_token {
+                    pool.fee_growth_token += fee_growth;
+                } else {
+                    pool.fee_growth_base += fee_growth;
+                }
+                fee_left -= step_fee;
+                remaining -= &step_in;
+            }
+
+            if reached {
+                pool.sqrt_price = target;
+                if initialized {
+                    let tick = self.cross_cl_tick(pool, next_tick, commit);
+                    let liquidity = if token_in_is_token {
+                        BigInt::from(pool.liquidity.clone()) - tick.liquidity_net
+                    } else {
+                        BigInt::from(pool.liquidity.clone()) + tick.liquidity_net
+                    };
+                    pool.liquidity = liquidity.into_big_uint().unwrap_or_else(|| sc_panic!(ERROR_INSUFFICIENT_LIQUIDITY));
+                }
+                pool.current_tick = if token_in_is_token { next_tick - 1 } else { next_tick };
+            } else {
+                let tick = self.get_cl_tick_at_sqrt_price(&pool.sqrt_price);
+                pool.current_tick = if token_in_is_token {
+                    tick.clamp(next_tick, pool.current_tick)
+                } else {
+                    tick.clamp(pool.current_tick, next_tick - 1)
+                };
+            }
+        }
+
+        amount_out
+    }
+
+    // next tick of the swap within the bitmap word: the closest initialized one at or below the current tick when
+    // going down, above it when going up, or else the last tick of the word. leaving the tick range without
+    // meeting an initialized tick means there is no liquidity left
+    fn get_next_cl_tick(&self, pool: &ClPool<Self::Api>, going_down: bool) -> (i64, bool) {
+        let compressed = pool.current_tick.div_euclid(pool.tick_spacing) + if going_down { 0 } else { 1 };
+        let bit = compressed.rem_euclid(CL_TICK_WORD_BITS);
+        let word_start = compressed - bit;
+        let bitmap = self.cl_tick_bitmap(pool.id, word_start / CL_TICK_WORD_BITS).get();
+        let (next, initialized) = if going_down {
+            let masked = bitmap & (u64::MAX >> (CL_TICK_WORD_BITS - 1 - bit));
+            if masked != 0 {
+                (word_start + CL_TICK_WORD_BITS - 1 - masked.leading_zeros() as i64, true)
+            } else {
+                (word_start, false)
+            }
+        } else {
+            let masked = bitmap & (u64::MAX << bit);
+            if masked != 0 {
+                (word_start + masked.trailing_zeros() as i64, true)
+            } else {
+                (word_start + CL_TICK_WORD_BITS - 1, false)
+            }
+        };
+
+        let tick = next * pool.tick_spacing;
+        require!(initialized || (tick > -CL_MAX_TICK && tick < CL_MAX_TICK), ERROR_INSUFFICIENT_LIQUIDITY);
+
+        (tick, initialized)
+    }
+
+    // flips the fee growths outside the tick, the current tick moving to its other side
+    fn cross_cl_tick(&self, pool: &ClPool<Self::Api>, tick: i64, commit: bool) -> ClTick<Self::Api> {
+        let mut info = self.cl_ticks(pool.id, tick).get();
+        if commit {
+            info.fee_growth_outside_token = &pool.fee_growth_token - &info.fee_growth_outside_token;
+            info.fee_growth_outside_base = &pool.fee_growth_base - &info.fee_growth_outside_base;
+            self.cl_ticks(pool.id, tick).set(&info);
+        }
+
+        info
+    }
+
+    fn update_cl_tick(&self, pool: &ClPool<Self::Api>, tick: i64, liquidity: &BigUint, adding: bool, is_upper: bool) {
+        let mapper = self.cl_ticks(pool.id, tick);
+        let mut info = if mapper.is_empty() {
+            // all the fees so far are assumed to have been earned below a tick initialized under the current one
+            let below = tick <= pool.current_tick;
+            self.flip_cl_tick(pool, tick);
+            ClTick {
+                liquidity_gross: BigUint::zero(),
+                liquidity_net: BigInt::zero(),
+                fee_growth_outside_token: if below { pool.fee_growth_token.clone() } else { BigUint::zero() },
+                fee_growth_outside_base: if below { pool.fee_growth_base.clone() } else { BigUint::zero() },
+            }
+        } else {
+            mapper.get()
+        };
+
+        // liquidity comes in when crossing a lower tick upwards and goes out when crossing an upper one
+        let liquidity_delta = BigInt::from(liquidity.clone());
+        if adding {
+            info.liquidity_gross += liquidity;
+        } else {
+            info.liquidity_gross -= liquidity;
+        }
+        if adding != is_upper {
+            info.liquidity_net += liquidity_delta;
+        } else {
+            info.liquidity_net -= liquidity_delta;
+        }
+
+        if info.liquidity_gross == 0 {
+            mapper.clear();
+            self.flip_cl_tick(pool, tick);
+        } else {
+            mapper.set(&info);
+        }
+    }
+
+    // marks a tick initialized on its first position and clears it when its last one goes away
+    fn flip_cl_tick(&self, pool: &ClPool<Self::Api>, tick: i64) {
+        let compressed = tick / pool.tick_spacing;
+        self.cl_tick_bitmap(pool.id, compressed.div_euclid(CL_TICK_WORD_BITS))
+            .update(|bitmap| *bitmap ^= 1u64 << compressed.rem_euclid(CL_TICK_WORD_BITS));
+    }
+
+    fn get_cl_fee_growth_inside(&self, pool: &ClPool<Self::Api>, tick_lower: i64, tick_upper: i64) -> (BigInt, BigInt) {
+        let lower = self.cl_ticks(pool.id, tick_lower).get();
+        let upper = self.cl_ticks(pool.id, tick_upper).get();
+        let inside_token = self.get_cl_range_growth(
+            pool,
+            tick_lower,
+            tick_upper,
+            &pool.fee_growth_token,
+            &lower.fee_growth_outside_token,
+            &upper.fee_growth_outside_token,
+        );
+        let inside_base = self.get_cl_range_growth(
+            pool,
+            tick_lower,
+            tick_upper,
+            &pool.fee_growth_base,
+            &lower.fee_growth_outside_base,
+            &upper.fee_growth_outside_base,
+        );
+
+        (inside_token, inside_base)
+    }
+
+    fn get_cl_range_growth(
+        &self,
+        pool: &ClPool<Self::Api>,
+        tick_lower: i64,
+        tick_upper: i64,
+        global: &BigUint,
+        lower_outside: &BigUint,
+        upper_outside: &BigUint,
+    ) -> BigInt {
+        let below = if pool.current_tick >= tick_lower { lower_outside.clone() } else { global - lower_outside };
+        let above = if pool.current_tick < tick_upper { upper_outside.clone() } else { global - upper_outside };
+
+        BigInt::from(global.clone()) - BigInt::from(below) - BigInt::from(above)
+    }
+
+    // fees earned since the snapshots of the position, with the current fee growths inside its range
+    fn get_cl_fees_owed(
+        &self,
+        pool: &ClPool<Self::Api>,
+        position: &ClPosition<Self::Api>,
+    ) -> (BigUint, BigUint, BigInt, BigInt) {
+        let (inside_token, inside_base) = self.get_cl_fee_growth_inside(pool, position.tick_lower, position.tick_upper);
+        let liquidity = BigInt::from(position.liquidity.clone());
+        let one = BigInt::from(BigUint::from(FIXED_POINT_ONE));
+        let fees_token = (&(&liquidity * &(&inside_token - &position.fee_growth_inside_token)) / &one)
+            .into_big_uint()
+            .unwrap_or_else(BigUint::zero);
+        let fees_base = (&(&liquidity * &(&inside_base - &position.fee_growth_inside_base)) / &one)
+            .into_big_uint()
+            .unwrap_or_else(BigUint::zero);
+
+        (fees_token, fees_base, inside_token, inside_base)
+    }
+
+    // sqrt(1.0001 ^ tick) = e ^ (tick * ln(1.0001) / 2), scaled by FIXED_POINT_ONE
+    fn get_cl_sqrt_price_at_tick(&self, tick: i64) -> BigUint {
+        let exponent = BigUint::from(tick.unsigned_abs()) * CL_LN_TICK_BASE / 2u64;
+        let sqrt_price = self.exp_fixed_point(&exponent);
+        if tick >= 0 {
+            return sqrt_price;
+        }
+
+        BigUint::from(FIXED_POINT_ONE) * FIXED_POINT_ONE / sqrt_price
+    }
+
+    // highest tick whose sqrt price is not above sqrt_price. the log estimate is corrected against the
+    // exact tick prices, so that ranges always agree with get_cl_sqrt_price_at_tick
+    fn get_cl_tick_at_sqrt_price(&self, sqrt_price: &BigUint) -> i64 {
+        let one = BigUint::from(FIXED_POINT_ONE);
+        let mut tick = if sqrt_price >= &one {
+            (self.ln_fixed_point(sqrt_price) * 2u64 / CL_LN_TICK_BASE).to_u64().unwrap_or_default() as i64
+        } else {
+            let ln: BigUint = self.ln_fixed_point(&(&one * &one / sqrt_price)) * 2u64;
+            -(((ln + CL_LN_TICK_BASE - 1u64) / CL_LN_TICK_BASE).to_u64().unwrap_or_default() as i64)
+        };
+        while tick < CL_MAX_TICK && &self.get_cl_sqrt_price_at_tick(tick + 1) <= sqrt_price {
+            tick += 1;
+        }
+        while tick > -CL_MAX_TICK && &self.get_cl_sqrt_price_at_tick(tick) > sqrt_price {
+            tick -= 1;
+        }
+
+        tick
+    }
+
+    // token amount between two sqrt prices for a liquidity: L * (b - a) / (a * b)
+    fn get_cl_token_delta(&self, sqrt_a: &BigUint, sqrt_b: &BigUint, liquidity: &BigUint, round_up: bool) -> BigUint {
+        let numerator = liquidity * &(sqrt_b - sqrt_a) * FIXED_POINT_ONE;
+        let denominator = sqrt_a * sqrt_b;
+        if round_up {
+            return (numerator + &denominator - 1u64) / denominator;
+        }
+
+        numerator / denominator
+    }
+
+    // base amount between two sqrt prices for a liquidity: L * (b - a)
+    fn get_cl_base_delta(&self, sqrt_a: &BigUint, sqrt_b: &BigUint, liquidity: &BigUint, round_up: bool) -> BigUint {
+        let numerator = liquidity * &(sqrt_b - sqrt_a);
+        if round_up {
+            return (numerator + FIXED_POINT_ONE - 1u64) / FIXED_POINT_ONE;
+        }
+
+        numerator / FIXED_POINT_ONE
+    }
+
+    fn get_cl_liquidity_for_amounts(
+        &self,
+        sqrt_price: &BigUint,
+        sqrt_lower: &BigUint,
+        sqrt_upper: &BigUint,
+        token_amount: &BigUint,
+        base_amount: &BigUint,
+    ) -> BigUint {
+        let for_token = |sqrt_a: &BigUint| token_amount * sqrt_a * sqrt_upper / FIXED_POINT_ONE / (sqrt_upper - sqrt_a);
+        let for_base = |sqrt_b: &BigUint| base_amount * FIXED_POINT_ONE / (sqrt_b - sqrt_lower);
+        if sqrt_price <= sqrt_lower {
+            for_token(sqrt_lower)
+        } else if sqrt_price >= sqrt_upper {
+            for_base(sqrt_upper)
+        } else {
+            core::cmp::min(for_token(sqrt_price), for_base(sqrt_price))
+        }
+    }
+
+    // token and base amounts backing a liquidity over a range at the given price
+    fn get_cl_amounts(
+        &self,
+        sqrt_price: &BigUint,
+        sqrt_lower: &BigUint,
+        sqrt_upper: &BigUint,
+        liquidity: &BigUint,
+        round_up: bool,
+    ) -> (BigUint, BigUint) {
+        if sqrt_price <= sqrt_lower {
+            (self.get_cl_token_delta(sqrt_lower, sqrt_upper, liquidity, round_up), BigUint::zero())
+        } else if sqrt_price >= sqrt_upper {
+            (BigUint::zero(), self.get_cl_base_delta(sqrt_lower, sqrt_upper, liquidity, round_up))
+        } else {
+            (
+                self.get_cl_token_delta(sqrt_price, sqrt_upper, liquidity, round_up),
+                self.get_cl_base_delta(sqrt_lower, sqrt_price, liquidity, round_up),
+            )
+        }
+    }
+
+    // global LP and owner fees, see swap_cl
+    fn get_cl_fee_amounts(&self, amount: &BigUint) -> (BigUint, BigUint) {
+        (amount * self.lp_fee().get() / MAX_PERCENT, amount * self.owner_fee().get() / MAX_PERCENT)
+    }
+
+    fn is_cl_token_in(&self, pool: &ClPool<Self::Api>, token_in: &TokenIdentifier) -> bool {
+        require!(token_in == &pool.token || token_in == &pool.base_token, ERROR_WRONG_PAYMENT);
+
+        token_in == &pool.token
+    }
+
+    fn get_cl_pool(&self, pool_id: usize) -> ClPool<Self::Api> {
+        require!(!self.cl_pools(pool_id).is_empty(), ERROR_CL_POOL_NOT_FOUND);
+
+        self.cl_pools(pool_id).get()
+    }
+
+    fn get_cl_position_payment(&self) -> (u64, ClPosition<Self::Api>) {
+        let payment = self.call_value().single_esdt();
+        require!(
+            !self.cl_position_token().is_empty() && payment.token_identifier == self.cl_position_token().get(),
+            ERROR_WRONG_PAYMENT
+        );
+
+        let position = self.blockchain().get_token_attributes(&payment.token_identifier, payment.token_nonce);
+
+        (payment.token_nonce, position)
+    }
+
+    fn mint_cl_position(&self, to: &ManagedAddress, position: &ClPosition<Self::Api>) -> u64 {
+        let token = self.cl_position_token().get();
+        let nonce = self.send().esdt_nft_create_compact(&token, &BigUint::from(1u64), position);
+        self.send().direct_esdt(to, &token, nonce, &BigUint::from(1u64));
+
+        nonce
+    }
+
+    #[view(getClAmountOut)]
+    fn get_cl_amount_out(&self, pool_id: usize, token_in: TokenIdentifier, amount_in: BigUint) -> BigUint {
+        let mut pool = self.get_cl_pool(pool_id);
+        let token_in_is_token = self.is_cl_token_in(&pool, &token_in);
+        let (lp_fee, owner_fee) = self.get_cl_fee_amounts(&amount_in);
+
+        self.cl_swap(&mut pool, token_in_is_token, &(&amount_in - &lp_fee - &owner_fee), &lp_fee, false)
+    }
+
+    // token and base amounts a position is worth right now, followed by its uncollected fees
+    #[view(getClPositionAmounts)]
+    fn get_cl_position_amounts(&self, position: ClPosition<Self::Api>) -> MultiValue4<BigUint, BigUint, BigUint, BigUint> {
+        let pool = self.get_cl_pool(position.pool_id);
+        let sqrt_lower = self.get_cl_sqrt_price_at_tick(position.tick_lower);
+        let sqrt_upper = self.get_cl_sqrt_price_at_tick(position.tick_upper);
+        let (token_amount, base_amount) =
+            self.get_cl_amounts(&pool.sqrt_price, &sqrt_lower, &sqrt_upper, &position.liquidity, false);
+        let (fees_token, fees_base, _, _) = self.get_cl_fees_owed(&pool, &position);
+
+        (token_amount, base_amount, fees_token, fees_base).into()
+    }
+}
@@ -0,0 +1,181 @@
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
+
+multiversx_sc::imports!();
+
+// stop-loss and take-profit orders. they trigger on the pair TWAP rather than on the spot price, so a single
+// manipulated block can't set them off, and are filled by keepers through the regular pair swap
+#[multiversx_sc::module]
+pub trait ConditionalOrdersModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
++super::swap::SwapModule
++super::limit_orders::LimitOrdersModule
+{
+    // trigger_price is the price of the payment token in token_out, scaled by PRICE_PRECISION, averaged over
+    // twap_window seconds. max_slippage, in MAX_PERCENT, bounds the output against that average
+    #[payable("*")]
+    #[endpoint(placeConditionalOrder)]
+    fn place_conditional_order(
+        &self,
+        token_out: TokenIdentifier,
+        order_type: ConditionalOrderType,
+        trigger_price: BigUint,
+        twap_window: u64,
+        max_slippage: u64,
+        expires_at: u64,
+    ) -> u64 {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(trigger_price > 0 && max_slippage < MAX_PERCENT, ERROR_WRONG_ORDER);
+        // observations are at least a period apart and the newest one can be up to a period old, so the ring only
+        // ever reaches back (capacity - 1) periods. longer windows would make the order impossible to execute
+        require!(
+            twap_window >= PRICE_OBSERVATION_PERIOD &&
+            twap_window <= (PRICE_OBSERVATIONS_CAPACITY as u64 - 1) * PRICE_OBSERVATION_PERIOD,
+            ERROR_WRONG_TWAP_WINDOW
+        );
+        require!(expires_at > self.blockchain().get_block_timestamp(), ERROR_ORDER_EXPIRED);
+
+        let payment = self.get_single_payment();
+        require!(payment.amount > 0, ERROR_ZERO_AMOUNT);
+
+        let pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
+            Some(pair) => pair,
+            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+        };
+
+        let caller = self.blockchain().get_caller();
+        let id = self.last_conditional_order_id().get() + 1;
+        let order = ConditionalOrder {
+            id,
+            owner: caller.clone(),
+            pair_id: pair.id,
+            order_type,
+            token_in: payment.token_identifier,
+            amount_in: payment.amount,
+            token_out,
+            trigger_price,
+            twap_window,
+            max_slippage,
+            expires_at,
+        };
+        self.last_conditional_order_id().set(id);
+        self.conditional_orders(id).set(&order);
+        self.user_conditional_orders(&caller).insert(id);
+        self.add_escrow(&order.token_in, &order.amount_in);
+        self.conditional_order_placed_event(id, &caller, &order);
+
+        id
+    }
+
+    // the owner can cancel at any time, anyone else once the order has expired. tokens go back to the owner
+    #[endpoint(cancelConditionalOrder)]
+    fn cancel_conditional_order(&self, order_id: u64) {
+        require!(!self.conditional_orders(order_id).is_empty(), ERROR_ORDER_NOT_FOUND);
+
+        let order = self.conditional_orders(order_id).get();
+        require!(
+            order.owner == self.blockchain().get_caller() || self.blockchain().get_block_timestamp() > order.expires_at,
+            ERROR_NOT_ORDER_OWNER
+        );
+
+        self.remove_conditional_order(&order);
+        self.send_payout(&order.owner, &order.token_in, &order.amount_in);
+        self.conditional_order_cancelled_event(order_id, &order.owner);
+    }
+
+    // fills a triggered order, paying the caller the keeper fee out of the output
+    #[endpoint(executeConditionalOrder)]
+    fn execute_conditional_order(&self, order_id: u64) -> BigUint {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.conditional_orders(order_id).is_empty(), ERROR_ORDER_NOT_FOUND);
+
+        let order = self.conditional_orders(order_id).get();
+        require!(self.blockchain().get_block_timestamp() <= order.expires_at, ERROR_ORDER_EXPIRED);
+
+        let mut pair = self.pairs(order.pair_id).get();
+        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+
+        let twap = self.get_order_twap(&pair, &order);
+        require!(self.is_conditional_order_triggered(&order, &twap), ERROR_ORDER_NOT_TRIGGERED);
+
+        let amount_out = self.swap_pair_fixed_input(&mut pair, &order.token_in, &order.amount_in);
+        let keeper_fee = &amount_out * self.order_keeper_fee().get() / MAX_PERCENT;
+        let owner_amount = &amount_out - &keeper_fee;
+        let expected_amount = &order.amount_in * &twap / PRICE_PRECISION;
+        require!(
+            &owner_amount * MAX_PERCENT >= expected_amount * (MAX_PERCENT - order.max_slippage),
+            ERROR_MAX_SLIPPAGE_EXCEEDED
+        );
+
+        let caller = self.blockchain().get_caller();
+        self.remove_conditional_order(&order);
+        self.send_payout(&order.owner, &order.token_out, &owner_amount);
+        self.send_payout(&caller, &order.token_out, &keeper_fee);
+        self.conditional_order_executed_event(order_id, &caller, &OrderExecutedEvent {
+            amount_out: owner_amount.clone(),
+            keeper_fee,
+        });
+
+        owner_amount
+    }
+
+    // average price of the order's input token in its output token
+    fn get_order_twap(&self, pair: &Pair<Self::Api>, order: &ConditionalOrder<Self::Api>) -> BigUint {
+        let (token_price, base_price) = self.get_twap(pair.id, order.twap_window).into_tuple();
+        if order.token_in == pair.token {
+            token_price
+        } else {
+            base_price
+        }
+    }
+
+    fn is_conditional_order_triggered(&self, order: &ConditionalOrder<Self::Api>, twap: &BigUint) -> bool {
+        match order.order_type {
+            ConditionalOrderType::StopLoss => twap <= &order.trigger_price,
+            ConditionalOrderType::TakeProfit => twap >= &order.trigger_price,
+        }
+    }
+
+    fn remove_conditional_order(&self, order: &ConditionalOrder<Self::Api>) {
+        self.conditional_orders(order.id).clear();
+        self.user_conditional_orders(&order.owner).swap_remove(&order.id);
+        self.remove_escrow(&order.token_in, &order.amount_in);
+    }
+
+    #[view(getConditionalOrders)]
+    fn get_conditional_orders(&self, address: ManagedAddress) -> ManagedVec<ConditionalOrder<Self::Api>> {
+        let mut orders = ManagedVec::new();
+        for id in self.user_conditional_orders(&address).iter() {
+            orders.push(self.conditional_orders(id).get());
+        }
+
+        orders
+    }
+
+    #[view(isConditionalOrderTriggered)]
+    fn is_conditional_order_triggered_view(&self, order_id: u64) -> bool {
+        require!(!self.conditional_orders(order_id).is_empty(), ERROR_ORDER_NOT_FOUND);
+
+        let order = self.conditional_orders(order_id).get();
+        if self.blockchain().get_block_timestamp() > order.expires_at {
+            return false;
+        }
+
+        let twap = self.get_order_twap(&self.pairs(order.pair_id).get(), &order);
+
+        self.is_conditional_order_triggered(&order, &twap)
+    }
+
+    #[view(getConditionalOrder)]
+    #[storage_mapper("conditional_orders")]
+    fn conditional_orders(&self, id: u64) -> SingleValueMapper<ConditionalOrder<Self::Api>>;
+
+    #[storage_mapper("user_conditional_orders")]
+    fn user_conditional_orders(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    #[view(getLastConditionalOrderId)]
+    #[storage_mapper("last_conditional_order_id")]
+    fn last_conditional_order_id(&self) -> SingleValueMapper<u64>;
+}
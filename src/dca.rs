@@ -0,0 +1,167 @@
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait DcaModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
++super::swap::SwapModule
+{
+    // deposits the whole amount to be swapped into token_out, amount_per_swap at a time, every interval
+    // seconds. min_price is the lowest token_out per token_in accepted, scaled by PRICE_PRECISION
+    #[payable("*")]
+    #[endpoint(createDca)]
+    fn create_dca(&self, token_out: TokenIdentifier, amount_per_swap: BigUint, interval: u64, min_price: BigUint) -> u64 {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(interval > 0, ERROR_WRONG_DCA_SCHEDULE);
+
+        let payment = self.get_single_payment();
+        require!(amount_per_swap > 0 && amount_per_swap <= payment.amount, ERROR_WRONG_DCA_SCHEDULE);
+
+        let pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
+            Some(pair) => pair,
+            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+        };
+
+        let caller = self.blockchain().get_caller();
+        let id = self.last_dca_id().get() + 1;
+        let schedule = DcaSchedule {
+            id,
+            owner: caller.clone(),
+            pair_id: pair.id,
+            token_in: payment.token_identifier,
+            token_out,
+            amount_per_swap,
+            interval,
+            min_price,
+            remaining: payment.amount,
+            accumulated: BigUint::zero(),
+            last_execution: 0,
+        };
+        self.last_dca_id().set(id);
+        self.dca_schedules(id).set(&schedule);
+        self.user_dca_schedules(&caller).insert(id);
+        self.add_escrow(&schedule.token_in, &schedule.remaining);
+        self.dca_created_event(id, &caller, &schedule);
+
+        id
+    }
+
+    // swaps the next installment of a schedule once its interval has elapsed. anyone can call it
+    #[endpoint(executeDca)]
+    fn execute_dca(&self, schedule_id: u64) -> BigUint {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.dca_schedules(schedule_id).is_empty(), ERROR_DCA_NOT_FOUND);
+
+        let mut schedule = self.dca_schedules(schedule_id).get();
+        require!(schedule.remaining > 0, ERROR_DCA_FINISHED);
+        require!(
+            schedule.last_execution == 0 ||
+            self.blockchain().get_block_timestamp() >= schedule.last_execution + schedule.interval,
+            ERROR_DCA_TOO_EARLY
+        );
+
+        let mut pair = self.pairs(schedule.pair_id).get();
+        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+
+        let amount_in = core::cmp::min(schedule.amount_per_swap.clone(), schedule.remaining.clone());
+        let amount_out = self.swap_pair_fixed_input(&mut pair, &schedule.token_in, &amount_in);
+        require!(amount_out >= &amount_in * &schedule.min_price / PRICE_PRECISION, ERROR_DCA_PRICE_TOO_LOW);
+
+        self.remove_escrow(&schedule.token_in, &amount_in);
+        self.add_escrow(&schedule.token_out, &amount_out);
+        schedule.remaining -= &amount_in;
+        schedule.accumulated += &amount_out;
+        schedule.last_execution = self.blockchain().get_block_timestamp();
+        self.dca_schedules(schedule_id).set(&schedule);
+        self.dca_executed_event(schedule_id, &self.blockchain().get_caller(), &DcaExecutedEvent {
+            amount_in,
+            amount_out: amount_out.clone(),
+            remaining: schedule.remaining,
+            accumulated: schedule.accumulated,
+        });
+
+        amount_out
+    }
+
+    // pays out the tokens bought so far. a finished schedule is removed once claimed
+    #[endpoint(claimDca)]
+    fn claim_dca(&self, schedule_id: u64) {
+        let mut schedule = self.get_owned_dca(schedule_id);
+        let amount = schedule.accumulated.clone();
+        self.remove_escrow(&schedule.token_out, &amount);
+        schedule.accumulated = BigUint::zero();
+        if schedule.remaining == 0 {
+            self.remove_dca(&schedule);
+        } else {
+            self.dca_schedules(schedule_id).set(&schedule);
+        }
+
+        self.send_payout(&schedule.owner, &schedule.token_out, &amount);
+        self.dca_claimed_event(schedule_id, &schedule.owner, &amount);
+    }
+
+    // stops a schedule, paying out both the tokens not swapped yet and the ones bought
+    #[endpoint(cancelDca)]
+    fn cancel_dca(&self, schedule_id: u64) {
+        let schedule = self.get_owned_dca(schedule_id);
+        self.remove_escrow(&schedule.token_in, &schedule.remaining);
+        self.remove_escrow(&schedule.token_out, &schedule.accumulated);
+        self.remove_dca(&schedule);
+
+        self.send_payout(&schedule.owner, &schedule.token_in, &schedule.remaining);
+        self.send_payout(&schedule.owner, &schedule.token_out, &schedule.accumulated);
+        self.dca_cancelled_event(schedule_id, &schedule.owner);
+    }
+
+    fn get_owned_dca(&self, schedule_id: u64) -> DcaSchedule<Self::Api> {
+        require!(!self.dca_schedules(schedule_id).is_empty(), ERROR_DCA_NOT_FOUND);
+
+        let schedule = self.dca_schedules(schedule_id).get();
+        require!(schedule.owner == self.blockchain().get_caller(), ERROR_NOT_DCA_OWNER);
+
+        schedule
+    }
+
+    fn remove_dca(&self, schedule: &DcaSchedule<Self::Api>) {
+        self.dca_schedules(schedule.id).clear();
+        self.user_dca_schedules(&schedule.owner).swap_remove(&schedule.id);
+    }
+
+    #[view(getDcaSchedules)]
+    fn get_dca_schedules(&self, address: ManagedAddress) -> ManagedVec<DcaSchedule<Self::Api>> {
+        let mut schedules = ManagedVec::new();
+        for id in self.user_dca_schedules(&address).iter() {
+            schedules.push(self.dca_schedules(id).get());
+        }
+
+        schedules
+    }
+
+    // timestamp from which executeDca can run for the schedule
+    #[view(getNextDcaExecution)]
+    fn get_next_dca_execution(&self, schedule_id: u64) -> u64 {
+        require!(!self.dca_schedules(schedule_id).is_empty(), ERROR_DCA_NOT_FOUND);
+
+        let schedule = self.dca_schedules(schedule_id).get();
+        if schedule.last_execution == 0 {
+            return 0;
+        }
+
+        schedule.last_execution + schedule.interval
+    }
+
+    #[view(getDcaSchedule)]
+    #[storage_mapper("dca_schedules")]
+    fn dca_schedules(&self, id: u64) -> SingleValueMapper<DcaSchedule<Self::Api>>;
+
+    #[storage_mapper("user_dca_schedules")]
+    fn user_dca_schedules(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    #[view(getLastDcaId)]
+    #[storage_mapper("last_dca_id")]
+    fn last_dca_id(&self) -> SingleValueMapper<u64>;
+}
@@ -0,0 +1,282 @@
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait FarmingModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
++super::liquidity::LiquidityModule
++super::swap::SwapModule
++super::zap::ZapModule
+{
+    // funds a reward program for the stakers of the pair's LP token, paying out the whole payment evenly
+    // between start_time and end_time. a pair keeps its reward token, and a new program can only start once
+    // the previous one ended
+    #[payable("*")]
+    #[endpoint(fundFarm)]
+    fn fund_farm(&self, pair_id: usize, start_time: u64, end_time: u64) {
+        self.only_owner_or_launchpad();
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let payment = self.get_single_payment();
+        let pair = self.pairs(pair_id).get();
+        require!(payment.token_identifier != pair.lp_token, ERROR_WRONG_REWARD_TOKEN);
+
+        let now = self.blockchain().get_block_timestamp();
+        require!(start_time >= now && end_time > start_time, ERROR_WRONG_FARM_SCHEDULE);
+
+        let reward_per_second = &payment.amount / (end_time - start_time);
+        require!(reward_per_second > 0, ERROR_WRONG_FARM_SCHEDULE);
+
+        let mut farm = if self.farms(pair_id).is_empty() {
+            Farm {
+                pair_id,
+                reward_token: payment.token_identifier.clone(),
+                reward_per_second: BigUint::zero(),
+                start_time: 0,
+                end_time: 0,
+                total_staked: BigUint::zero(),
+                reward_per_share: BigUint::zero(),
+                last_update: now,
+                funded: BigUint::zero(),
+                distributed: BigUint::zero(),
+            }
+        } else {
+            let mut farm = self.farms(pair_id).get();
+            require!(farm.reward_token == payment.token_identifier, ERROR_WRONG_REWARD_TOKEN);
+            self.update_farm(&mut farm);
+            require!(now >= farm.end_time, ERROR_FARM_IN_PROGRESS);
+
+            farm
+        };
+        farm.reward_per_second = reward_per_second;
+        farm.start_time = start_time;
+        farm.end_time = end_time;
+        farm.funded += &payment.amount;
+        self.farms(pair_id).set(&farm);
+        self.add_escrow(&payment.token_identifier, &payment.amount);
+
+        self.farm_funded_event(pair_id, &self.blockchain().get_caller(), &farm);
+    }
+
+    #[payable("*")]
+    #[endpoint(stakeLp)]
+    fn stake_lp(&self) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let payment = self.call_value().single_esdt();
+        let pair = match self.get_pair_by_lp_token(&payment.token_identifier) {
+            Some(pair) => pair,
+            None => sc_panic!(ERROR_WRONG_PAYMENT),
+        };
+        require!(!self.farms(pair.id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        self.add_farm_stake(pair.id, &caller, &payment.amount);
+        self.lp_staked_event(pair.id, &caller, &payment.amount);
+    }
+
+    // pending rewards are kept and can still be claimed after unstaking everything
+    #[endpoint(unstakeLp)]
+    fn unstake_lp(&self, pair_id: usize, amount: BigUint) {
+        require!(!self.farms(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        let (mut farm, mut position) = self.settle_farm_position(pair_id, &caller);
+        require!(amount > 0 && amount <= position.amount, ERROR_NOT_ENOUGH_STAKED);
+
+        farm.total_staked -= &amount;
+        position.amount -= &amount;
+        position.reward_debt = &position.amount * &farm.reward_per_share / FIXED_POINT_ONE;
+        self.farms(pair_id).set(&farm);
+        self.save_farm_position(pair_id, &caller, &position);
+
+        let lp_token = self.pairs(pair_id).get().lp_token;
+        self.send().direct_esdt(&caller, &lp_token, 0, &amount);
+        self.lp_unstaked_event(pair_id, &caller, &amount);
+    }
+
+    #[endpoint(claimRewards)]
+    fn claim_rewards(&self, pair_id: usize) -> BigUint {
+        require!(!self.farms(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        let (farm, mut position) = self.settle_farm_position(pair_id, &caller);
+        let amount = position.pending.clone();
+        require!(amount > 0, ERROR_NO_REWARDS);
+
+        position.pending = BigUint::zero();
+        self.farms(pair_id).set(&farm);
+        self.save_farm_position(pair_id, &caller, &position);
+        self.remove_escrow(&farm.reward_token, &amount);
+
+        self.send_payout(&caller, &farm.reward_token, &amount);
+        self.farm_rewards_claimed_event(pair_id, &caller, &amount);
+
+        amount
+    }
+
+    // zaps the pending rewards into the pair and stakes the LP tokens. only works for farms paying one of the
+    // pair's own tokens
+    #[endpoint(compoundRewards)]
+    fn compound_rewards(&self, pair_id: usize, min_lp_out: BigUint) -> BigUint {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.farms(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        let (farm, mut position) = self.settle_farm_position(pair_id, &caller);
+        let amount = position.pending.clone();
+        require!(amount > 0, ERROR_NO_REWARDS);
+
+        position.pending = BigUint::zero();
+        self.farms(pair_id).set(&farm);
+        self.save_farm_position(pair_id, &caller, &position);
+        self.remove_escrow(&farm.reward_token, &amount);
+
+        let mut pair = self.get_zap_pair(pair_id, &farm.reward_token);
+        let (token_amount, base_amount) = self.swap_zap_amounts(&mut pair, &farm.reward_token, &amount);
+        let (_, _, lp_amount) = self.mint_pair_liquidity(&mut pair, &caller, token_amount, base_amount);
+        require!(lp_amount >= min_lp_out, ERROR_INSUFFICIENT_LP_AMOUNT);
+
+        self.add_farm_stake(pair_id, &caller, &lp_amount);
+        self.farm_rewards_compounded_event(pair_id, &caller, &amount, &lp_amount);
+
+        lp_amount
+    }
+
+    // sends back the rewards that were not distributed, because nobody was staking for a while
+    #[endpoint(withdrawFarmLeftover)]
+    fn withdraw_farm_leftover(&self, pair_id: usize) {
+        self.only_owner_or_launchpad();
+        require!(!self.farms(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let mut farm = self.farms(pair_id).get();
+        self.update_farm(&mut farm);
+        require!(self.blockchain().get_block_timestamp() >= farm.end_time, ERROR_FARM_IN_PROGRESS);
+
+        let amount = &farm.funded - &farm.distributed;
+        farm.funded = farm.distributed.clone();
+        self.farms(pair_id).set(&farm);
+        self.remove_escrow(&farm.reward_token, &amount);
+
+        let caller = self.blockchain().get_caller();
+        self.send_payout(&caller, &farm.reward_token, &amount);
+        self.farm_leftover_withdrawn_event(pair_id, &caller, &amount);
+    }
+
+    // accrues the rewards emitted since the last update, capped at end_time. nothing accrues while no LP is staked,
+    // so those rewards stay undistributed
+    fn update_farm(&self, farm: &mut Farm<Self::Api>) {
+        let now = core::cmp::min(self.blockchain().get_block_timestamp(), farm.end_time);
+        let from = core::cmp::max(farm.last_update, farm.start_time);
+        if now > from && farm.total_staked > 0 {
+            let reward = &farm.reward_per_second * (now - from);
+            farm.reward_per_share += &reward * FIXED_POINT_ONE / &farm.total_staked;
+            farm.distributed += reward;
+        }
+        if now > farm.last_update {
+            farm.last_update = now;
+        }
+    }
+
+    // updates the farm and moves the position's accrued rewards into pending
+    fn settle_farm_position(&self, pair_id: usize, address: &ManagedAddress) -> (Farm<Self::Api>, FarmPosition<Self::Api>) {
+        let mut farm = self.farms(pair_id).get();
+        self.update_farm(&mut farm);
+
+        let mut position = if self.farm_positions(pair_id, address).is_empty() {
+            FarmPosition {
+                amount: BigUint::zero(),
+                reward_debt: BigUint::zero(),
+                pending: BigUint::zero(),
+            }
+        } else {
+            self.farm_positions(pair_id, address).get()
+        };
+        position.pending += &position.amount * &farm.reward_per_share / FIXED_POINT_ONE - &position.reward_debt;
+        position.reward_debt = &position.amount * &farm.reward_per_share / FIXED_POINT_ONE;
+
+        (farm, position)
+    }
+
+    fn add_farm_stake(&self, pair_id: usize, address: &ManagedAddress, amount: &BigUint) {
+        let (mut farm, mut position) = self.settle_farm_position(pair_id, address);
+        farm.total_staked += amount;
+        position.amount += amount;
+        position.reward_debt = &position.amount * &farm.reward_per_share / FIXED_POINT_ONE;
+        self.farms(pair_id).set(&farm);
+        self.save_farm_position(pair_id, address, &position);
+    }
+
+    fn save_farm_position(&self, pair_id: usize, address: &ManagedAddress, position: &FarmPosition<Self::Api>) {
+        if position.amount == 0 && position.pending == 0 {
+            self.farm_positions(pair_id, address).clear();
+        } else {
+            self.farm_positions(pair_id, address).set(position);
+        }
+    }
+
+    #[view(getPendingRewards)]
+    fn get_pending_rewards(&self, pair_id: usize, address: ManagedAddress) -> BigUint {
+        require!(!self.farms(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let (_, position) = self.settle_farm_position(pair_id, &address);
+
+        position.pending
+    }
+
+    // yearly rewards over the value of the staked LP tokens, both in the pair's base token, in MAX_PERCENT.
+    // zero when the reward token can't be priced through the pair itself or a pair with the same base token
+    #[view(getFarmApr)]
+    fn get_farm_apr(&self, pair_id: usize) -> u64 {
+        require!(!self.farms(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let farm = self.farms(pair_id).get();
+        let pair = self.pairs(pair_id).get();
+        let now = self.blockchain().get_block_timestamp();
+        if now >= farm.end_time || farm.total_staked == 0 || pair.lp_supply == 0 {
+            return 0;
+        }
+
+        let yearly_rewards = &farm.reward_per_second * SECONDS_PER_YEAR;
+        let yearly_value = if farm.reward_token == pair.base_token {
+            yearly_rewards
+        } else {
+            let price_pair = if farm.reward_token == pair.token {
+                Some(pair.clone())
+            } else {
+                self.get_pair_by_tickers(&pair.base_token, &farm.reward_token)
+                    .filter(|price_pair| price_pair.base_token == pair.base_token)
+            };
+            match price_pair {
+                Some(price_pair) if price_pair.liquidity_token > 0 => self.get_base_value(&price_pair, &yearly_rewards),
+                _ => return 0,
+            }
+        };
+        let (_, weight_base) = self.get_current_weights(&pair);
+        let staked_value = &pair.liquidity_base * MAX_PERCENT / weight_base * &farm.total_staked / &pair.lp_supply;
+        if staked_value == 0 {
+            return 0;
+        }
+
+        (yearly_value * MAX_PERCENT / staked_value).to_u64().unwrap_or(u64::MAX)
+    }
+
+    // spot value of a token amount in the pair's base token
+    fn get_base_value(&self, pair: &Pair<Self::Api>, token_amount: &BigUint) -> BigUint {
+        let (weight_token, weight_base) = self.get_current_weights(pair);
+        token_amount * &pair.liquidity_base * weight_token / (&pair.liquidity_token * weight_base)
+    }
+
+    #[view(getFarm)]
+    #[storage_mapper("farms")]
+    fn farms(&self, pair_id: usize) -> SingleValueMapper<Farm<Self::Api>>;
+
+    #[view(getFarmPosition)]
+    #[storage_mapper("farm_positions")]
+    fn farm_positions(&self, pair_id: usize, address: &ManagedAddress) -> SingleValueMapper<FarmPosition<Self::Api>>;
+}
@@ -0,0 +1,133 @@
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait FlashLoanModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
+{
+    // lends `amount` of a token held as pair reserves and calls `endpoint` on the caller, which has to send back
+    // the amount plus the flash loan fee in the same call. reserves of inactive pairs are never lent, and empty ones
+    // are left out so they don't get any of the fee
+    #[endpoint(flashLoan)]
+    fn flash_loan(
+        &self,
+        token: TokenIdentifier,
+        amount: BigUint,
+        endpoint: ManagedBuffer,
+        args: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+        require!(amount > 0, ERROR_ZERO_AMOUNT);
+
+        let mut lending_pairs: ManagedVec<Pair<Self::Api>> = ManagedVec::new();
+        let mut available = BigUint::zero();
+        for pair_id in self.token_pairs(&token).values() {
+            let pair = self.pairs(pair_id).get();
+            let reserve = self.get_pair_reserve(&pair, &token);
+            if pair.state == PairState::Inactive || reserve == 0 {
+                continue;
+            }
+
+            available += reserve;
+            lending_pairs.push(pair);
+        }
+        require!(amount <= available, ERROR_NO_LIQUIDITY);
+
+        let fee = &amount * self.flash_loan_fee().get() / MAX_PERCENT;
+        let caller = self.blockchain().get_caller();
+        self.lock();
+        let back_transfers = self.tx()
+            .to(&caller)
+            .raw_call(endpoint)
+            .arguments_raw(args.to_arg_buffer())
+            .single_esdt(&token, 0, &amount)
+            .returns(ReturnsBackTransfers)
+            .sync_call();
+        self.unlock();
+
+        require!(back_transfers.total_egld_amount == 0, ERROR_WRONG_PAYMENT);
+        let mut repaid = BigUint::zero();
+        for payment in back_transfers.esdt_payments.iter() {
+            require!(payment.token_identifier == token, ERROR_WRONG_PAYMENT);
+            repaid += &payment.amount;
+        }
+        require!(repaid >= &amount + &fee, ERROR_FLASH_LOAN_NOT_REPAID);
+
+        let paid_fee = repaid - &amount;
+        let lp_fee = self.lp_fee().get();
+        let owner_fee = self.owner_fee().get();
+        let owner_fee_amount = if lp_fee + owner_fee == 0 {
+            BigUint::zero()
+        } else {
+            &paid_fee * owner_fee / (lp_fee + owner_fee)
+        };
+        let lp_fee_amount = &paid_fee - &owner_fee_amount;
+        self.add_cummulated_fee(&token, &owner_fee_amount);
+        self.distribute_flash_loan_fee(&lending_pairs, &token, &available, &lp_fee_amount);
+
+        self.flash_loan_event(&caller, &token, &FlashLoanEvent {
+            amount,
+            lp_fee: lp_fee_amount,
+            owner_fee: owner_fee_amount,
+        });
+    }
+
+    // splits the LP part of the fee between the lending pairs, pro rata to the reserves they lent from.
+    // rounding dust goes to the last pair
+    fn distribute_flash_loan_fee(
+        &self,
+        lending_pairs: &ManagedVec<Pair<Self::Api>>,
+        token: &TokenIdentifier,
+        total_reserve: &BigUint,
+        fee_amount: &BigUint,
+    ) {
+        if fee_amount == &0 {
+            return;
+        }
+
+        let mut distributed = BigUint::zero();
+        for (index, mut pair) in lending_pairs.iter().enumerate() {
+            let pair_fee = if index == lending_pairs.len() - 1 {
+                fee_amount - &distributed
+            } else {
+                fee_amount * &self.get_pair_reserve(&pair, token) / total_reserve
+            };
+            distributed += &pair_fee;
+
+            self.update_price_cumulatives(&pair);
+            self.refresh_lbp_weights(&pair);
+            if &pair.token == token {
+                pair.liquidity_token += pair_fee;
+            } else {
+                pair.liquidity_base += pair_fee;
+            }
+            self.pairs(pair.id).set(&pair);
+        }
+    }
+
+    fn get_pair_reserve(&self, pair: &Pair<Self::Api>, token: &TokenIdentifier) -> BigUint {
+        if &pair.token == token {
+            pair.liquidity_token.clone()
+        } else {
+            pair.liquidity_base.clone()
+        }
+    }
+
+    #[view(getFlashLoanFee)]
+    #[storage_mapper("flash_loan_fee")]
+    fn flash_loan_fee(&self) -> SingleValueMapper<u64>;
+
+    #[only_owner]
+    #[endpoint(setFlashLoanFee)]
+    fn set_flash_loan_fee(&self, fee: u64) {
+        require!(fee < MAX_PERCENT, ERROR_WRONG_FEE);
+
+        self.flash_loan_fee().set(fee);
+        self.flash_loan_fee_changed_event(fee);
+    }
+}
@@ -1,10 +1,12 @@
-use crate::common::{config, consts::*, errors::*};
+use crate::common::{config, consts::*, errors::*, events};
+use crate::proxies::wrapper_proxy::ProxyTrait as _;
 
 multiversx_sc::imports!();
 
 #[multiversx_sc::module]
 pub trait HelpersModule:
 config::ConfigModule
++events::EventsModule
 {
     fn quote(
         &self,
@@ -15,76 +17,446 @@ config::ConfigModule
         &(token_amount * base_liquidity) / token_liquidity
     }
 
+    // weights is (weight in, weight out), only used by weighted pairs
     fn get_amount_out_no_fee(
         &self,
+        pair_id: usize,
         amount_in: &BigUint,
         liquidity_in: &BigUint,
         liquidity_out: &BigUint,
+        weights: (u64, u64),
+        multipliers: (u64, u64),
     ) -> BigUint {
+        match self.try_get_amount_out_no_fee(pair_id, amount_in, liquidity_in, liquidity_out, weights, multipliers) {
+            Some(amount_out) => amount_out,
+            None => sc_panic!(ERROR_MAX_RATIO_EXCEEDED),
+        }
+    }
+
+    // None when amount_in is above the part of the reserves a weighted pair takes in a single swap
+    fn try_get_amount_out_no_fee(
+        &self,
+        pair_id: usize,
+        amount_in: &BigUint,
+        liquidity_in: &BigUint,
+        liquidity_out: &BigUint,
+        weights: (u64, u64),
+        multipliers: (u64, u64),
+    ) -> Option<BigUint> {
+        let curve = self.pair_curve(pair_id).get();
+        if curve == config::CurveType::Weighted {
+            return self.try_get_weighted_amount_out(amount_in, liquidity_in, liquidity_out, weights);
+        }
+        if curve == config::CurveType::StableSwap {
+            let amp = self.get_pair_amp(pair_id);
+            let (multiplier_in, multiplier_out) = multipliers;
+            let scaled_in = liquidity_in * multiplier_in;
+            let scaled_out = liquidity_out * multiplier_out;
+            let invariant = self.get_stable_invariant(amp, &scaled_in, &scaled_out);
+            let new_scaled_out = self.get_stable_reserve(amp, &(scaled_in + amount_in * multiplier_in), &invariant);
+            // rounds against the trader, like the constant product formula
+            if &new_scaled_out + 1u64 >= scaled_out {
+                return Some(BigUint::zero());
+            }
+
+            return Some((scaled_out - new_scaled_out - 1u64) / multiplier_out);
+        }
+
         let numerator = amount_in * liquidity_out;
         let denominator = liquidity_in + amount_in;
 
-        numerator / denominator
+        Some(numerator / denominator)
     }
 
     fn get_amount_in_no_fee(
         &self,
+        pair_id: usize,
         amount_out: &BigUint,
         liquidity_in: &BigUint,
         liquidity_out: &BigUint,
+        weights: (u64, u64),
+        multipliers: (u64, u64),
     ) -> BigUint {
+        require!(amount_out < liquidity_out, ERROR_NO_LIQUIDITY);
+
+        let curve = self.pair_curve(pair_id).get();
+        if curve == config::CurveType::Weighted {
+            return self.get_weighted_amount_in(amount_out, liquidity_in, liquidity_out, weights);
+        }
+        if curve == config::CurveType::StableSwap {
+            let amp = self.get_pair_amp(pair_id);
+            let (multiplier_in, multiplier_out) = multipliers;
+            let scaled_in = liquidity_in * multiplier_in;
+            let scaled_out = liquidity_out * multiplier_out;
+            let invariant = self.get_stable_invariant(amp, &scaled_in, &scaled_out);
+            let new_scaled_in = self.get_stable_reserve(amp, &(scaled_out - amount_out * multiplier_out), &invariant);
+            // newton's error can leave the new reserve under the old one for dust amounts
+            if new_scaled_in <= scaled_in {
+                return BigUint::from(1u64);
+            }
+
+            return (new_scaled_in - scaled_in) / multiplier_in + 1u64;
+        }
+
         let numerator = liquidity_in * amount_out;
         let denominator = liquidity_out - amount_out;
 
         (numerator / denominator) + &BigUint::from(1u64)
     }
 
+    // amounts the swap functions of the swap module would produce, without touching the storage
     fn get_amount_out(
         &self,
+        pair_id: usize,
         amount_in: &BigUint,
         liquidity_in: &BigUint,
         liquidity_out: &BigUint,
         fee_in: bool,
+        weights: (u64, u64),
+        multipliers: (u64, u64),
     ) -> BigUint {
-        let total_fee = self.lp_fee().get() + self.owner_fee().get();
+        match self.try_get_amount_out(pair_id, amount_in, liquidity_in, liquidity_out, fee_in, weights, multipliers) {
+            Some(amount_out) => amount_out,
+            None => sc_panic!(ERROR_MAX_RATIO_EXCEEDED),
+        }
+    }
+
+    // get_amount_out for quotes that must not fail, like the route search and the order checks
+    fn try_get_amount_out(
+        &self,
+        pair_id: usize,
+        amount_in: &BigUint,
+        liquidity_in: &BigUint,
+        liquidity_out: &BigUint,
+        fee_in: bool,
+        weights: (u64, u64),
+        multipliers: (u64, u64),
+    ) -> Option<BigUint> {
         if fee_in {
-            let amount_in_with_fee = amount_in * (MAX_PERCENT - total_fee);
-            let numerator = &amount_in_with_fee * liquidity_out;
-            let denominator = liquidity_in * MAX_PERCENT + amount_in_with_fee;
+            let (_, _, total_fee) = self.get_fee_amounts(pair_id, amount_in, true);
 
-            numerator / denominator
+            self.try_get_amount_out_no_fee(pair_id, &(amount_in - &total_fee), liquidity_in, liquidity_out, weights, multipliers)
         } else {
-            let amount_out_no_fee = self.get_amount_out_no_fee(amount_in, liquidity_in, liquidity_out);
+            let amount_out_no_fee = self.try_get_amount_out_no_fee(pair_id, amount_in, liquidity_in, liquidity_out, weights, multipliers)?;
+            let (_, _, total_fee) = self.get_fee_amounts(pair_id, &amount_out_no_fee, true);
 
-            amount_out_no_fee * (MAX_PERCENT - total_fee) / MAX_PERCENT
+            Some(amount_out_no_fee - total_fee)
         }
     }
 
     fn get_amount_in(
         &self,
+        pair_id: usize,
         amount_out: &BigUint,
         liquidity_in: &BigUint,
         liquidity_out: &BigUint,
         fee_in: bool,
+        weights: (u64, u64),
+        multipliers: (u64, u64),
     ) -> BigUint {
-        let total_fee = self.lp_fee().get() + self.owner_fee().get();
         if fee_in {
-            let numerator = amount_out * liquidity_in * MAX_PERCENT;
-            let denominator = (liquidity_out - amount_out) * (MAX_PERCENT - total_fee);
+            let amount_in_no_fee = self.get_amount_in_no_fee(pair_id, amount_out, liquidity_in, liquidity_out, weights, multipliers);
+            let (_, _, total_fee) = self.get_fee_amounts(pair_id, &amount_in_no_fee, false);
 
-            (numerator / denominator) + 1u64
+            amount_in_no_fee + total_fee
         } else {
-            let amount_out_with_fee = amount_out * MAX_PERCENT / (MAX_PERCENT - total_fee);
+            let (_, _, total_fee) = self.get_fee_amounts(pair_id, amount_out, false);
+
+            self.get_amount_in_no_fee(pair_id, &(amount_out + &total_fee), liquidity_in, liquidity_out, weights, multipliers)
+        }
+    }
 
-            self.get_amount_in_no_fee(&amount_out_with_fee, liquidity_in, liquidity_out)
+    // stable swap invariant D of a two token pool, solving 4A(x + y) + D = 4AD + D^3 / 4xy with newton's method
+    fn get_stable_invariant(&self, amp: u64, x: &BigUint, y: &BigUint) -> BigUint {
+        if x == &0 || y == &0 {
+            return BigUint::zero();
+        }
+
+        let sum = x + y;
+        let ann = amp * 4;
+        let mut invariant = sum.clone();
+        for _ in 0..STABLE_MAX_ITERATIONS {
+            let d_p = &invariant * &invariant / (x * 2u64) * &invariant / (y * 2u64);
+            let previous = invariant.clone();
+            invariant = (&sum * ann + &d_p * 2u64) * &invariant / (&invariant * (ann - 1) + d_p * 3u64);
+            if self.is_converged(&invariant, &previous) {
+                return invariant;
+            }
+        }
+
+        sc_panic!(ERROR_STABLE_MATH_NOT_CONVERGED);
+    }
+
+    // reserve of the other token that keeps the invariant when one reserve becomes `reserve`
+    fn get_stable_reserve(&self, amp: u64, reserve: &BigUint, invariant: &BigUint) -> BigUint {
+        let ann = amp * 4;
+        let c = invariant * invariant / (reserve * 2u64) * invariant / (ann * 2);
+        let b = reserve + &(invariant / ann);
+        let mut y = invariant.clone();
+        for _ in 0..STABLE_MAX_ITERATIONS {
+            let denominator = &y * 2u64 + &b;
+            require!(&denominator > invariant, ERROR_STABLE_MATH_NOT_CONVERGED);
+
+            let previous = y.clone();
+            y = (&y * &y + &c) / (denominator - invariant);
+            if self.is_converged(&y, &previous) {
+                return y;
+            }
+        }
+
+        sc_panic!(ERROR_STABLE_MATH_NOT_CONVERGED);
+    }
+
+    fn is_converged(&self, value: &BigUint, previous: &BigUint) -> bool {
+        if value > previous {
+            value - previous <= 1u64
+        } else {
+            previous - value <= 1u64
+        }
+    }
+
+    // (multiplier in, multiplier out) of a swap selling token_in to a stable pair
+    fn get_swap_multipliers(&self, pair: &config::Pair<Self::Api>, token_in: &TokenIdentifier) -> (u64, u64) {
+        let (token_multiplier, base_multiplier) = self.get_precision_multipliers(pair.id);
+        if token_in == &pair.token {
+            (token_multiplier, base_multiplier)
+        } else {
+            (base_multiplier, token_multiplier)
+        }
+    }
+
+    // (weight in, weight out) of a swap selling token_in to the pair
+    fn get_swap_weights(&self, pair: &config::Pair<Self::Api>, token_in: &TokenIdentifier) -> (u64, u64) {
+        let (weight_token, weight_base) = self.get_current_weights(pair);
+        if token_in == &pair.token {
+            (weight_token, weight_base)
+        } else {
+            (weight_base, weight_token)
+        }
+    }
+
+    // (weight token, weight base) at the current timestamp, following the lbp schedule if there is one
+    fn get_current_weights(&self, pair: &config::Pair<Self::Api>) -> (u64, u64) {
+        if self.lbp_schedule(pair.id).is_empty() {
+            return self.get_stored_weights(pair.id);
+        }
+
+        let schedule = self.lbp_schedule(pair.id).get();
+        let now = self.blockchain().get_block_timestamp();
+        let weight_token = if now <= schedule.start_time {
+            schedule.start_weight_token
+        } else if now >= schedule.end_time {
+            schedule.end_weight_token
+        } else {
+            let elapsed = now - schedule.start_time;
+            let duration = schedule.end_time - schedule.start_time;
+            if schedule.end_weight_token > schedule.start_weight_token {
+                schedule.start_weight_token + (schedule.end_weight_token - schedule.start_weight_token) * elapsed / duration
+            } else {
+                schedule.start_weight_token - (schedule.start_weight_token - schedule.end_weight_token) * elapsed / duration
+            }
+        };
+
+        (weight_token, MAX_PERCENT - weight_token)
+    }
+
+    // moves the stored weights of a pair in lbp to the current ones, and turns it into a normal pair once the
+    // schedule is over: constant product if it ends at 50/50, weighted otherwise
+    fn refresh_lbp_weights(&self, pair: &config::Pair<Self::Api>) {
+        if self.lbp_schedule(pair.id).is_empty() {
+            return;
+        }
+
+        let (weight_token, weight_base) = self.get_current_weights(pair);
+        self.pair_weights(pair.id).set(config::PairWeights { weight_token, weight_base });
+        if self.blockchain().get_block_timestamp() < self.lbp_schedule(pair.id).get().end_time {
+            return;
+        }
+
+        if weight_token == weight_base {
+            self.pair_curve(pair.id).set(config::CurveType::ConstantProduct);
+        }
+        self.lbp_schedule(pair.id).clear();
+        self.lbp_ended_event(pair.id, self.pair_curve(pair.id).get());
+    }
+
+    // balancer weighted product: out = liquidity_out * (1 - (liquidity_in / (liquidity_in + amount_in)) ^ (weight_in / weight_out)).
+    // None above WEIGHTED_MAX_IN_RATIO of liquidity_in
+    fn try_get_weighted_amount_out(
+        &self,
+        amount_in: &BigUint,
+        liquidity_in: &BigUint,
+        liquidity_out: &BigUint,
+        weights: (u64, u64),
+    ) -> Option<BigUint> {
+        if amount_in * MAX_PERCENT > liquidity_in * WEIGHTED_MAX_IN_RATIO {
+            return None;
+        }
+
+        let one = BigUint::from(FIXED_POINT_ONE);
+        let base = liquidity_in * &one / (liquidity_in + amount_in);
+        let power = self.pow_fixed_point(&base, weights.0, weights.1);
+
+        Some(liquidity_out * &(one - power) / FIXED_POINT_ONE)
+    }
+
+    // balancer weighted product: in = liquidity_in * ((liquidity_out / (liquidity_out - amount_out)) ^ (weight_out / weight_in) - 1)
+    fn get_weighted_amount_in(
+        &self,
+        amount_out: &BigUint,
+        liquidity_in: &BigUint,
+        liquidity_out: &BigUint,
+        weights: (u64, u64),
+    ) -> BigUint {
+        require!(amount_out * MAX_PERCENT <= liquidity_out * WEIGHTED_MAX_OUT_RATIO, ERROR_MAX_RATIO_EXCEEDED);
+
+        let one = BigUint::from(FIXED_POINT_ONE);
+        let base = (liquidity_out - amount_out) * &one / liquidity_out;
+        let power = self.pow_fixed_point(&base, weights.1, weights.0);
+        let ratio = &one * &one / power;
+
+        liquidity_in * &(ratio - one) / FIXED_POINT_ONE + 1u64
+    }
+
+    // base ^ (numerator / denominator) for a FIXED_POINT_ONE scaled base in (0, 1]. the integer part of the exponent
+    // is done by multiplication, the fractional part a by the binomial series of (1 - x) ^ a, whose terms after the
+    // first all have the same sign for 0 < a < 1
+    fn pow_fixed_point(&self, base: &BigUint, numerator: u64, denominator: u64) -> BigUint {
+        let one = BigUint::from(FIXED_POINT_ONE);
+        require!(base > &0 && base <= &one, ERROR_MAX_RATIO_EXCEEDED);
+
+        let mut result = one.clone();
+        for _ in 0..numerator / denominator {
+            result = result * base / FIXED_POINT_ONE;
+        }
+
+        let fraction = numerator % denominator;
+        if fraction == 0 {
+            return result;
+        }
+
+        // a = fraction / denominator, x = 1 - base. term_1 = a * x, term_k = term_k-1 * (k - 1 - a) * x / k
+        let x = &one - base;
+        let mut term = &x * fraction / denominator;
+        let mut sum = term.clone();
+        for k in 2..POW_MAX_ITERATIONS as u64 {
+            term = term * ((k - 1) * denominator - fraction) / denominator * &x / FIXED_POINT_ONE / k;
+            if term < POW_PRECISION {
+                break;
+            }
+
+            sum += &term;
+        }
+        require!(sum < one, ERROR_MAX_RATIO_EXCEEDED);
+
+        result * (one - sum) / FIXED_POINT_ONE
+    }
+
+    // e ^ x for a FIXED_POINT_ONE scaled x: e ^ whole part by multiplication, e ^ fraction by its taylor series
+    fn exp_fixed_point(&self, x: &BigUint) -> BigUint {
+        let one = BigUint::from(FIXED_POINT_ONE);
+        let whole = x / &one;
+        require!(whole <= MAX_EXP_EXPONENT, ERROR_EXPONENT_TOO_LARGE);
+
+        let mut result = one.clone();
+        for _ in 0..whole.to_u64().unwrap_or_default() {
+            result = result * E_FIXED_POINT / FIXED_POINT_ONE;
+        }
+
+        let fraction = x.clone() - &whole * &one;
+        let mut term = one.clone();
+        let mut sum = one;
+        for k in 1..POW_MAX_ITERATIONS as u64 {
+            term = term * &fraction / FIXED_POINT_ONE / k;
+            if term < POW_PRECISION {
+                break;
+            }
+
+            sum += &term;
+        }
+
+        result * sum / FIXED_POINT_ONE
+    }
+
+    // ln(y) for a FIXED_POINT_ONE scaled y >= 1: whole e factors are taken out first, then
+    // ln(y) = 2 * (u + u^3 / 3 + u^5 / 5 + ...) with u = (y - 1) / (y + 1), which converges fast for y < e
+    fn ln_fixed_point(&self, y: &BigUint) -> BigUint {
+        let one = BigUint::from(FIXED_POINT_ONE);
+        require!(y >= &one, ERROR_EXPONENT_TOO_LARGE);
+
+        let mut y = y.clone();
+        let mut whole = 0u64;
+        while y >= E_FIXED_POINT {
+            y = y * FIXED_POINT_ONE / E_FIXED_POINT;
+            whole += 1;
+        }
+
+        let u = (&y - &one) * &one / (&y + &one);
+        let u_squared = &u * &u / &one;
+        let mut power = u.clone();
+        let mut sum = u;
+        for k in 1..POW_MAX_ITERATIONS as u64 {
+            power = power * &u_squared / FIXED_POINT_ONE;
+            let term = &power / (2 * k + 1);
+            if term < POW_PRECISION {
+                break;
+            }
+
+            sum += term;
+        }
+
+        one * whole + sum * 2u64
+    }
+
+    // whether the reserves, both scaled the same way, did not lose value against the old ones
+    fn is_invariant_kept(
+        &self,
+        pair: &config::Pair<Self::Api>,
+        new_token: &BigUint,
+        new_base: &BigUint,
+        old_token: &BigUint,
+        old_base: &BigUint,
+    ) -> bool {
+        match self.pair_curve(pair.id).get() {
+            config::CurveType::ConstantProduct => new_token * new_base >= old_token * old_base,
+            config::CurveType::StableSwap => {
+                let amp = self.get_pair_amp(pair.id);
+                let (token_multiplier, base_multiplier) = self.get_precision_multipliers(pair.id);
+                let new_invariant = self.get_stable_invariant(amp, &(new_token * token_multiplier), &(new_base * base_multiplier));
+
+                new_invariant >= self.get_stable_invariant(amp, &(old_token * token_multiplier), &(old_base * base_multiplier))
+            },
+            config::CurveType::Weighted => {
+                // (new_token / old_token) ^ weight_token * (new_base / old_base) ^ weight_base >= 1, with the side
+                // that shrank raised to its weight relative to the other one
+                if new_token >= old_token && new_base >= old_base {
+                    return true;
+                }
+                if new_token < old_token && new_base < old_base {
+                    return false;
+                }
+
+                let one = BigUint::from(FIXED_POINT_ONE);
+                let (weight_token, weight_base) = self.get_current_weights(pair);
+                let (low_ratio, high_ratio, low_weight, high_weight) = if new_token < old_token {
+                    (new_token * &one / old_token, new_base * &one / old_base, weight_token, weight_base)
+                } else {
+                    (new_base * &one / old_base, new_token * &one / old_token, weight_base, weight_token)
+                };
+                require!(&low_ratio * MAX_PERCENT >= &one * (MAX_PERCENT - WEIGHTED_MAX_OUT_RATIO), ERROR_MAX_RATIO_EXCEEDED);
+
+                self.pow_fixed_point(&low_ratio, low_weight, high_weight) * high_ratio >= &one * &one
+            },
         }
     }
 
     // returns lp fee, owner fee, total fee calculated from amount
-    fn get_fee_amounts(&self, amount: &BigUint, is_input: bool) -> (BigUint, BigUint, BigUint) {
-        let lp_fee = self.lp_fee().get();
-        let owner_fee = self.owner_fee().get();
+    fn get_fee_amounts(&self, pair_id: usize, amount: &BigUint, is_input: bool) -> (BigUint, BigUint, BigUint) {
+        let (lp_fee, owner_fee) = self.get_pair_fee_rates(pair_id);
         let total_fee = lp_fee + owner_fee;
+        if total_fee == 0 {
+            return (BigUint::zero(), BigUint::zero(), BigUint::zero());
+        }
 
         if is_input {
             (amount * lp_fee / MAX_PERCENT, amount * owner_fee / MAX_PERCENT, amount * total_fee / MAX_PERCENT)
@@ -97,6 +469,121 @@ config::ConfigModule
         }
     }
 
+    // swap fees are charged in the input token when it is a base token, otherwise in the output token
+    fn get_fee_token(&self, pair: &config::Pair<Self::Api>, token_in: &TokenIdentifier) -> TokenIdentifier {
+        if self.base_tokens().contains(token_in) {
+            token_in.clone()
+        } else if token_in == &pair.token {
+            pair.base_token.clone()
+        } else {
+            pair.token.clone()
+        }
+    }
+
+    fn add_cummulated_fee(&self, token: &TokenIdentifier, amount: &BigUint) {
+        if amount == &0 {
+            return;
+        }
+
+        let mut fees = self.cummulated_fees();
+        let new_amount = fees.get(token).unwrap_or_default() + amount;
+        fees.insert(token.clone(), new_amount);
+    }
+
+    fn add_escrow(&self, token: &TokenIdentifier, amount: &BigUint) {
+        let mut escrowed = self.escrowed_tokens();
+        let new_amount = escrowed.get(token).unwrap_or_default() + amount;
+        escrowed.insert(token.clone(), new_amount);
+    }
+
+    fn remove_escrow(&self, token: &TokenIdentifier, amount: &BigUint) {
+        let mut escrowed = self.escrowed_tokens();
+        let new_amount = escrowed.get(token).unwrap_or_default() - amount;
+        if new_amount == 0 {
+            escrowed.remove(token);
+        } else {
+            escrowed.insert(token.clone(), new_amount);
+        }
+    }
+
+    // set while a flash swap or loan hands control to the borrower, so the pairs can't be touched meanwhile
+    fn lock(&self) {
+        self.reentrancy_lock().set(true);
+    }
+
+    fn unlock(&self) {
+        self.reentrancy_lock().clear();
+    }
+
+    fn require_not_locked(&self) {
+        require!(!self.reentrancy_lock().get(), ERROR_REENTRANCY);
+    }
+
+    fn require_deadline(&self, deadline: OptionalValue<u64>) {
+        if let OptionalValue::Some(deadline) = deadline {
+            require!(self.blockchain().get_block_timestamp() <= deadline, ERROR_DEADLINE_PASSED);
+        }
+    }
+
+    // single payment of an endpoint, with EGLD wrapped into WEGLD on the fly
+    fn get_single_payment(&self) -> EsdtTokenPayment {
+        let payment = self.call_value().egld_or_single_esdt();
+        if payment.token_identifier.is_egld() {
+            return self.wrap_egld_payment(&payment.amount);
+        }
+
+        payment.unwrap_esdt()
+    }
+
+    // multi payment of an endpoint, with EGLD-000000 transfers wrapped into WEGLD on the fly
+    fn get_multi_payments(&self) -> ManagedVec<EsdtTokenPayment> {
+        let egld_token_id = TokenIdentifier::from(EGLD_000000_TOKEN_ID);
+        let mut payments = ManagedVec::new();
+        for payment in self.call_value().all_esdt_transfers().iter() {
+            if payment.token_identifier == egld_token_id {
+                payments.push(self.wrap_egld_payment(&payment.amount));
+            } else {
+                payments.push(payment);
+            }
+        }
+
+        payments
+    }
+
+    fn wrap_egld_payment(&self, amount: &BigUint) -> EsdtTokenPayment {
+        require!(!self.wrapper_address().is_empty(), ERROR_EGLD_WRAPPER_NOT_SET);
+        require!(amount > &0, ERROR_ZERO_AMOUNT);
+
+        self.wrapper_contract_proxy()
+            .contract(self.wrapper_address().get())
+            .wrap_egld()
+            .with_egld_transfer(amount.clone())
+            .execute_on_dest_context::<()>();
+
+        EsdtTokenPayment::new(self.wegld_token().get(), 0, amount.clone())
+    }
+
+    // sends tokens to an address, unwrapping WEGLD so it arrives as EGLD. WEGLD always leaves the contract as EGLD,
+    // whatever the caller paid with, so every payout of pair tokens goes through here. only LP tokens and position
+    // NFTs, which can't be WEGLD, are sent directly
+    fn send_payout(&self, to: &ManagedAddress, token: &TokenIdentifier, amount: &BigUint) {
+        if amount == &0 {
+            return;
+        }
+
+        if self.wegld_token().is_empty() || token != &self.wegld_token().get() {
+            self.send().direct_esdt(to, token, 0, amount);
+            return;
+        }
+
+        self.wrapper_contract_proxy()
+            .contract(self.wrapper_address().get())
+            .unwrap_egld()
+            .with_esdt_transfer(EsdtTokenPayment::new(token.clone(), 0, amount.clone()))
+            .execute_on_dest_context::<()>();
+        self.send().direct_egld(to, amount);
+    }
+
     fn only_owner_or_launchpad(&self) {
         let caller = self.blockchain().get_caller();
         require!(
@@ -0,0 +1,56 @@
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait LbpModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
+{
+    // makes an empty pair a liquidity bootstrapping pool. its weights follow the schedule on every swap and
+    // the pair becomes a normal one after end_time
+    #[endpoint(startLbp)]
+    fn start_lbp(
+        &self,
+        pair_id: usize,
+        start_weight_token: u64,
+        end_weight_token: u64,
+        start_time: u64,
+        end_time: u64,
+    ) {
+        self.only_owner_or_launchpad();
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(self.lbp_schedule(pair_id).is_empty(), ERROR_LBP_IN_PROGRESS);
+        for weight in [start_weight_token, end_weight_token] {
+            require!((MIN_WEIGHT..=MAX_PERCENT - MIN_WEIGHT).contains(&weight), ERROR_WRONG_WEIGHT);
+        }
+        require!(
+            start_time >= self.blockchain().get_block_timestamp() && end_time > start_time,
+            ERROR_WRONG_LBP_SCHEDULE
+        );
+
+        let pair = self.pairs(pair_id).get();
+        require!(pair.lp_supply == 0, ERROR_PAIR_NOT_EMPTY);
+        require!(pair.state != PairState::BondingCurve, ERROR_PAIR_IN_BONDING_CURVE);
+        require!(self.pair_curve(pair_id).get() != CurveType::StableSwap, ERROR_WRONG_CURVE);
+
+        let schedule = LbpSchedule { start_weight_token, end_weight_token, start_time, end_time };
+        self.lbp_schedule(pair_id).set(schedule);
+        self.pair_weights(pair_id).set(PairWeights {
+            weight_token: start_weight_token,
+            weight_base: MAX_PERCENT - start_weight_token,
+        });
+        self.pair_curve(pair_id).set(CurveType::Weighted);
+        self.pair_curve_changed_event(pair_id, CurveType::Weighted);
+        self.lbp_started_event(pair_id, &schedule);
+    }
+
+    // (weight token, weight base) the pair swaps with right now
+    #[view(getPairWeights)]
+    fn get_pair_weights(&self, pair_id: usize) -> MultiValue2<u64, u64> {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        self.get_current_weights(&self.pairs(pair_id).get()).into()
+    }
+}
@@ -5,7 +5,17 @@ multiversx_sc::imports!();
 pub mod common;
 pub mod swap;
 pub mod liquidity;
+pub mod flash_loan;
+pub mod zap;
+pub mod lbp;
+pub mod bonding_curve;
+pub mod concentrated_liquidity;
+pub mod limit_orders;
+pub mod dca;
+pub mod conditional_orders;
+pub mod farming;
 pub mod helpers;
+pub mod oracle;
 pub mod proxies;
 
 use common::{config::*, consts::*, errors::*};
@@ -13,9 +23,20 @@ use common::{config::*, consts::*, errors::*};
 #[multiversx_sc::contract]
 pub trait TFNDEXContract<ContractReader>:
 common::config::ConfigModule
++common::events::EventsModule
 +helpers::HelpersModule
++oracle::OracleModule
 +liquidity::LiquidityModule
 +swap::SwapModule
++flash_loan::FlashLoanModule
++zap::ZapModule
++lbp::LbpModule
++bonding_curve::BondingCurveModule
++concentrated_liquidity::ConcentratedLiquidityModule
++limit_orders::LimitOrdersModule
++dca::DcaModule
++conditional_orders::ConditionalOrdersModule
++farming::FarmingModule
 {
     #[init]
     fn init(&self) {
@@ -23,6 +44,8 @@ common::config::ConfigModule
 
     #[upgrade]
     fn upgrade(&self) {
+        self.index_existing_pairs();
+        self.sync_lp_roles_states();
     }
 
     #[payable("EGLD")]
@@ -46,28 +69,19 @@ common::config::ConfigModule
             lp_ticker = lp_ticker.copy_slice(0, 10).unwrap();
         }
         let issue_cost = self.call_value().egld_value().clone_value();
-        require!(issue_cost == BigUint::from(TOKEN_ISSUE_COST), ERROR_WRONG_ISSUE_COST);
+        require!(issue_cost == TOKEN_ISSUE_COST, ERROR_WRONG_ISSUE_COST);
 
+        // mint and burn are granted in the same call, so the pair can take liquidity as soon as it exists
         self.send()
             .esdt_system_sc_proxy()
-            .issue_fungible(
+            .issue_and_set_all_roles(
                 issue_cost,
                 lp_name,
                 lp_ticker,
-                BigUint::zero(),
-                FungibleTokenProperties {
-                    num_decimals: LP_TOKEN_DECIMALS,
-                    can_freeze: true,
-                    can_wipe: true,
-                    can_pause: true,
-                    can_mint: true,
-                    can_burn: true,
-                    can_change_owner: true,
-                    can_upgrade: true,
-                    can_add_special_roles: true,
-                },
+                EsdtTokenType::Fungible,
+                LP_TOKEN_DECIMALS,
             )
-            .with_callback(self.callbacks().lp_token_issue_callback(self.blockchain().get_caller(), &base_token, &token))
+            .with_callback(<Self as TFNDEXContract>::callbacks(self).lp_token_issue_callback(self.blockchain().get_caller(), &base_token, &token))
             .async_call_and_exit();
     }
 
@@ -93,7 +107,86 @@ common::config::ConfigModule
                     liquidity_base: BigUint::zero(),
                 };
                 self.last_pair_id().set(id + 1);
-                self.pairs(id).set(pair);
+                self.pairs(id).set(&pair);
+                self.index_pair(&pair);
+                self.pair_created_event(id, &caller, &pair);
+
+                self.lp_roles_state(id).set(LpRolesState::Granted);
+                self.lp_roles_granted_event(id);
+            }
+            ManagedAsyncCallResult::Err(_) => {
+                let issue_cost = self.call_value().egld_value();
+                self.send().direct_egld(&caller, &issue_cost);
+            }
+        }
+    }
+
+    // liquidity can't be added or removed until the contract can mint and burn the LP token. only used to
+    // retry, as new LP tokens get their roles when issued
+    fn set_lp_token_roles(&self, pair: &Pair<Self::Api>) {
+        let roles = [EsdtLocalRole::Mint, EsdtLocalRole::Burn];
+        self.send()
+            .esdt_system_sc_proxy()
+            .set_special_roles(self.blockchain().get_sc_address(), &pair.lp_token, roles.iter().cloned())
+            .with_callback(<Self as TFNDEXContract>::callbacks(self).lp_token_roles_callback(pair.id))
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn lp_token_roles_callback(
+        &self,
+        pair_id: usize,
+        #[call_result] result: ManagedAsyncCallResult<()>,
+    ) {
+        if let ManagedAsyncCallResult::Ok(()) = result {
+            self.lp_roles_state(pair_id).set(LpRolesState::Granted);
+            self.lp_roles_granted_event(pair_id);
+        }
+    }
+
+    // retries the role assignment for pairs whose roles were never granted
+    #[only_owner]
+    #[endpoint(setLpTokenRoles)]
+    fn retry_lp_token_roles(&self, pair_id: usize) {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(self.lp_roles_state(pair_id).get() == LpRolesState::Pending, ERROR_LP_ROLES_ALREADY_GRANTED);
+
+        self.set_lp_token_roles(&self.pairs(pair_id).get());
+    }
+
+    // NFT collection of the concentrated liquidity positions, shared by all pools
+    #[only_owner]
+    #[payable("EGLD")]
+    #[endpoint(issueClPositionToken)]
+    fn issue_cl_position_token(&self) {
+        require!(self.cl_position_token().is_empty(), ERROR_CL_POSITION_TOKEN_ALREADY_SET);
+
+        let issue_cost = self.call_value().egld_value().clone_value();
+        require!(issue_cost == TOKEN_ISSUE_COST, ERROR_WRONG_ISSUE_COST);
+
+        self.send()
+            .esdt_system_sc_proxy()
+            .issue_and_set_all_roles(
+                issue_cost,
+                ManagedBuffer::from(CL_POSITION_TOKEN_NAME),
+                ManagedBuffer::from(CL_POSITION_TOKEN_TICKER),
+                EsdtTokenType::NonFungible,
+                0,
+            )
+            .with_callback(<Self as TFNDEXContract>::callbacks(self).cl_position_token_issue_callback(self.blockchain().get_caller()))
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn cl_position_token_issue_callback(
+        &self,
+        caller: ManagedAddress,
+        #[call_result] result: ManagedAsyncCallResult<TokenIdentifier>,
+    ) {
+        match result {
+            ManagedAsyncCallResult::Ok(token) => {
+                self.cl_position_token().set(&token);
+                self.cl_position_token_issued_event(&token);
             }
             ManagedAsyncCallResult::Err(_) => {
                 let issue_cost = self.call_value().egld_value();
@@ -131,37 +224,214 @@ common::config::ConfigModule
     fn set_pair_active(&self, id: usize) {
         self.only_owner_or_launchpad();
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
         require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
 
         let mut pair = self.pairs(id).get();
         require!(pair.lp_supply > 0, ERROR_NO_LIQUIDITY);
 
         pair.state = PairState::Active;
-        self.pairs(id).set(pair);
+        self.pairs(id).set(&pair);
+        self.pair_state_changed_event(id, pair.state);
     }
 
     #[endpoint(setPairActiveNoSwap)]
     fn set_pair_active_no_swap(&self, id: usize) {
         self.only_owner_or_launchpad();
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
         require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
 
         let mut pair = self.pairs(id).get();
         require!(pair.lp_supply > 0, ERROR_NO_LIQUIDITY);
 
         pair.state = PairState::ActiveNoSwap;
-        self.pairs(id).set(pair);
+        self.pairs(id).set(&pair);
+        self.pair_state_changed_event(id, pair.state);
     }
 
     #[endpoint(setPairInactive)]
     fn set_pair_inactive(&self, id: usize) {
         self.only_owner_or_launchpad();
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
         require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
 
         let mut pair = self.pairs(id).get();
+        require!(pair.state != PairState::BondingCurve, ERROR_PAIR_IN_BONDING_CURVE);
+
         pair.state = PairState::Inactive;
-        self.pairs(id).set(pair);
+        self.pairs(id).set(&pair);
+        self.pair_state_changed_event(id, pair.state);
+    }
+
+    #[endpoint(setPairFees)]
+    fn set_pair_fees(&self, id: usize, lp_fee: u64, owner_fee: u64) {
+        self.only_owner_or_launchpad();
+        require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(lp_fee + owner_fee < MAX_PERCENT, ERROR_WRONG_FEE);
+
+        let fees = PairFees { lp_fee, owner_fee };
+        self.pair_fees(id).set(fees);
+        self.pair_fees_changed_event(id, &fees);
+    }
+
+    #[endpoint(removePairFees)]
+    fn remove_pair_fees(&self, id: usize) {
+        self.only_owner_or_launchpad();
+        require!(!self.pair_fees(id).is_empty(), ERROR_PAIR_FEES_NOT_SET);
+
+        self.pair_fees(id).clear();
+        self.pair_fees_changed_event(id, &self.get_pair_fees(id));
+    }
+
+    // pays out the owner fees of every token, fees collected in WEGLD being unwrapped to EGLD like any other payout
+    #[only_owner]
+    #[endpoint(withdrawFees)]
+    fn withdraw_fees(&self) {
+        let caller = self.blockchain().get_caller();
+        let mut payments: ManagedVec<EsdtTokenPayment> = ManagedVec::new();
+        for (token, amount) in self.cummulated_fees().iter() {
+            payments.push(EsdtTokenPayment::new(token, 0, amount));
+        }
+        self.cummulated_fees().clear();
+        for payment in payments.iter() {
+            self.send_payout(&caller, &payment.token_identifier, &payment.amount);
+        }
+        self.fees_withdrawn_event(&caller, &payments);
+    }
+
+    // starts the bonding curve phase of a new pair. the payment holds the tokens for sale plus reserved_tokens,
+    // which seed the pool together with the raised base once target_base is reached
+    #[payable("*")]
+    #[endpoint(startBondingCurve)]
+    fn start_bonding_curve(
+        &self,
+        id: usize,
+        curve_type: BondingCurveType,
+        initial_price: BigUint,
+        price_param: BigUint,
+        reserved_tokens: BigUint,
+        target_base: BigUint,
+    ) {
+        self.only_owner_or_launchpad();
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pairs(id).get();
+        require!(pair.state == PairState::ActiveNoSwap && pair.lp_supply == 0, ERROR_PAIR_NOT_EMPTY);
+        require!(self.pair_curve(id).get() == CurveType::ConstantProduct && self.lbp_schedule(id).is_empty(), ERROR_WRONG_CURVE);
+        require!(self.lp_roles_state(id).get() == LpRolesState::Granted, ERROR_LP_ROLES_NOT_GRANTED);
+
+        let payment = self.call_value().single_esdt();
+        require!(payment.token_identifier == pair.token && payment.amount > reserved_tokens, ERROR_WRONG_PAYMENT);
+        require!(initial_price > 0 && target_base > 0, ERROR_WRONG_BONDING_CURVE);
+        require!(curve_type == BondingCurveType::Linear || price_param > 0, ERROR_WRONG_BONDING_CURVE);
+        require!((&reserved_tokens * &target_base).sqrt() > MINIMUM_LIQUIDITY, ERROR_INSUFFICIENT_INITIAL_LIQUIDITY);
+
+        let curve = BondingCurve {
+            curve_type,
+            creator: self.blockchain().get_caller(),
+            initial_price,
+            price_param,
+            sale_supply: &payment.amount - &reserved_tokens,
+            reserved_tokens,
+            target_base,
+            tokens_sold: BigUint::zero(),
+            base_raised: BigUint::zero(),
+        };
+        // the target must be reachable by selling the whole supply
+        require!(
+            self.get_bonding_curve_cost(&curve, &BigUint::zero(), &curve.sale_supply) >= curve.target_base,
+            ERROR_WRONG_BONDING_CURVE
+        );
+
+        self.bonding_curves(id).set(&curve);
+        pair.state = PairState::BondingCurve;
+        self.pairs(id).set(&pair);
+        self.pair_state_changed_event(id, pair.state);
+        self.bonding_curve_started_event(id, &curve);
+    }
+
+    // turns an empty pair into a weighted pool, weights being in MAX_PERCENT
+    #[endpoint(setPairWeights)]
+    fn set_pair_weights(&self, id: usize, weight_token: u64, weight_base: u64) {
+        self.only_owner_or_launchpad();
+        require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(
+            weight_token >= MIN_WEIGHT && weight_base >= MIN_WEIGHT && weight_token + weight_base == MAX_PERCENT,
+            ERROR_WRONG_WEIGHT
+        );
+
+        require!(self.lbp_schedule(id).is_empty(), ERROR_LBP_IN_PROGRESS);
+
+        let pair = self.pairs(id).get();
+        require!(pair.lp_supply == 0, ERROR_PAIR_NOT_EMPTY);
+        require!(pair.state != PairState::BondingCurve, ERROR_PAIR_IN_BONDING_CURVE);
+        require!(self.pair_curve(id).get() != CurveType::StableSwap, ERROR_WRONG_CURVE);
+
+        self.pair_weights(id).set(PairWeights { weight_token, weight_base });
+        self.pair_curve(id).set(CurveType::Weighted);
+        self.pair_curve_changed_event(id, CurveType::Weighted);
+        self.pair_weights_changed_event(id, weight_token, weight_base);
+    }
+
+    // turns an empty pair of two base tokens into a stable swap pair. the decimals of both tokens set the
+    // precision multipliers that scale the reserves to the larger of the two before they go through the curve
+    #[only_owner]
+    #[endpoint(setPairStableSwap)]
+    fn set_pair_stable_swap(&self, id: usize, amp: u64, token_decimals: u32, base_decimals: u32) {
+        require!(!self.pairs(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(amp > 0 && amp <= MAX_AMP, ERROR_WRONG_AMP);
+        require!(token_decimals <= MAX_STABLE_DECIMALS && base_decimals <= MAX_STABLE_DECIMALS, ERROR_WRONG_DECIMALS);
+
+        let pair = self.pairs(id).get();
+        require!(pair.lp_supply == 0, ERROR_PAIR_NOT_EMPTY);
+        require!(pair.state != PairState::BondingCurve, ERROR_PAIR_IN_BONDING_CURVE);
+        require!(self.pair_curve(id).get() != CurveType::Weighted, ERROR_WRONG_CURVE);
+        require!(self.base_tokens().contains(&pair.token), ERROR_WRONG_BASE_TOKEN);
+
+        let now = self.blockchain().get_block_timestamp();
+        let ramp = AmpRamp { initial_amp: amp, future_amp: amp, initial_time: now, future_time: now };
+        let decimals = core::cmp::max(token_decimals, base_decimals);
+        let multipliers = PrecisionMultipliers {
+            token_multiplier: 10u64.pow(decimals - token_decimals),
+            base_multiplier: 10u64.pow(decimals - base_decimals),
+        };
+        self.pair_amp_ramp(id).set(ramp);
+        self.pair_precision_multipliers(id).set(multipliers);
+        self.pair_curve(id).set(CurveType::StableSwap);
+        self.pair_curve_changed_event(id, CurveType::StableSwap);
+        self.pair_amp_ramp_changed_event(id, &ramp);
+        self.pair_precision_multipliers_changed_event(id, &multipliers);
+    }
+
+    #[only_owner]
+    #[endpoint(rampPairAmp)]
+    fn ramp_pair_amp(&self, id: usize, future_amp: u64, future_time: u64) {
+        let initial_amp = self.get_pair_amp(id);
+        let now = self.blockchain().get_block_timestamp();
+        require!(future_time >= now + MIN_AMP_RAMP_TIME, ERROR_WRONG_RAMP_TIME);
+        require!(future_amp > 0 && future_amp <= MAX_AMP, ERROR_WRONG_AMP);
+        require!(
+            future_amp <= initial_amp * MAX_AMP_CHANGE && future_amp * MAX_AMP_CHANGE >= initial_amp,
+            ERROR_WRONG_AMP
+        );
+
+        let ramp = AmpRamp { initial_amp, future_amp, initial_time: now, future_time };
+        self.pair_amp_ramp(id).set(ramp);
+        self.pair_amp_ramp_changed_event(id, &ramp);
+    }
+
+    #[only_owner]
+    #[endpoint(stopPairAmpRamp)]
+    fn stop_pair_amp_ramp(&self, id: usize) {
+        let amp = self.get_pair_amp(id);
+        let now = self.blockchain().get_block_timestamp();
+
+        let ramp = AmpRamp { initial_amp: amp, future_amp: amp, initial_time: now, future_time: now };
+        self.pair_amp_ramp(id).set(ramp);
+        self.pair_amp_ramp_changed_event(id, &ramp);
     }
 
     #[only_owner]
@@ -170,7 +440,8 @@ common::config::ConfigModule
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
         require!(!self.base_tokens().contains(&token), ERROR_BASE_TOKEN_EXISTS);
 
-        self.base_tokens().insert(token);
+        self.base_tokens().insert(token.clone());
+        self.base_token_added_event(&token);
     }
 
     #[only_owner]
@@ -178,15 +449,9 @@ common::config::ConfigModule
     fn remove_base_token(&self, token: TokenIdentifier) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
         require!(self.base_tokens().contains(&token), ERROR_WRONG_BASE_TOKEN);
+        require!(self.base_token_pairs(&token).is_empty(), ERROR_BASE_TOKEN_IN_USE);
 
-        for pair_id in 0..self.last_pair_id().get() {
-            if self.pairs(pair_id).is_empty() {
-                continue;
-            }
-
-            let pair = self.pairs(pair_id).get();
-            require!(pair.base_token != token, ERROR_BASE_TOKEN_IN_USE);
-        }
         self.base_tokens().swap_remove(&token);
+        self.base_token_removed_event(&token);
     }
 }
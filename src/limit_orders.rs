@@ -0,0 +1,164 @@
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait LimitOrdersModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
++super::swap::SwapModule
+{
+    // escrows the payment until the pair returns at least min_amount_out of token_out for it
+    #[payable("*")]
+    #[endpoint(placeOrder)]
+    fn place_order(&self, token_out: TokenIdentifier, min_amount_out: BigUint) -> u64 {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(min_amount_out > 0, ERROR_ZERO_AMOUNT);
+
+        let payment = self.get_single_payment();
+        require!(payment.amount > 0, ERROR_ZERO_AMOUNT);
+
+        let pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
+            Some(pair) => pair,
+            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+        };
+        require!(pair.state != PairState::Inactive && pair.state != PairState::BondingCurve, ERROR_PAIR_NOT_ACTIVE);
+
+        let caller = self.blockchain().get_caller();
+        let id = self.last_order_id().get() + 1;
+        let order = LimitOrder {
+            id,
+            owner: caller.clone(),
+            pair_id: pair.id,
+            token_in: payment.token_identifier,
+            amount_in: payment.amount,
+            token_out,
+            min_amount_out,
+            created_at: self.blockchain().get_block_timestamp(),
+        };
+        self.last_order_id().set(id);
+        self.orders(id).set(&order);
+        self.user_orders(&caller).insert(id);
+        self.add_escrow(&order.token_in, &order.amount_in);
+        self.order_placed_event(id, &caller, &order);
+
+        id
+    }
+
+    #[endpoint(cancelOrder)]
+    fn cancel_order(&self, order_id: u64) {
+        require!(!self.orders(order_id).is_empty(), ERROR_ORDER_NOT_FOUND);
+
+        let order = self.orders(order_id).get();
+        let caller = self.blockchain().get_caller();
+        require!(order.owner == caller, ERROR_NOT_ORDER_OWNER);
+
+        self.remove_order(&order);
+        self.send_payout(&caller, &order.token_in, &order.amount_in);
+        self.order_cancelled_event(order_id, &caller);
+    }
+
+    // fills the given orders of the pair that the current price allows, skipping the others, and pays the
+    // caller the keeper fee out of each filled order's output
+    #[endpoint(executeOrders)]
+    fn execute_orders(&self, pair_id: usize, order_ids: MultiValueEncoded<u64>) -> usize {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pairs(pair_id).get();
+        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+
+        let caller = self.blockchain().get_caller();
+        let mut executed = 0;
+        for order_id in order_ids {
+            if self.orders(order_id).is_empty() {
+                continue;
+            }
+
+            let order = self.orders(order_id).get();
+            if order.pair_id != pair_id || !self.is_order_executable(&pair, &order) {
+                continue;
+            }
+
+            let amount_out = self.swap_pair_fixed_input(&mut pair, &order.token_in, &order.amount_in);
+            let keeper_fee = &amount_out * self.order_keeper_fee().get() / MAX_PERCENT;
+            let owner_amount = &amount_out - &keeper_fee;
+            self.remove_order(&order);
+            self.send_payout(&order.owner, &order.token_out, &owner_amount);
+            self.send_payout(&caller, &order.token_out, &keeper_fee);
+            self.order_executed_event(order_id, &caller, &OrderExecutedEvent {
+                amount_out: owner_amount,
+                keeper_fee,
+            });
+            executed += 1;
+        }
+
+        executed
+    }
+
+    // an order is filled when its whole amount, less the keeper fee, returns at least min_amount_out. orders the
+    // pair can't take at all, like ones above the max ratio of a weighted pair, are never executable
+    fn is_order_executable(&self, pair: &Pair<Self::Api>, order: &LimitOrder<Self::Api>) -> bool {
+        let amount_out = match self.try_get_pair_amount_out(pair, &order.token_in, &order.amount_in) {
+            Some(amount_out) => amount_out,
+            None => return false,
+        };
+        let keeper_fee = &amount_out * self.order_keeper_fee().get() / MAX_PERCENT;
+
+        amount_out - keeper_fee >= order.min_amount_out
+    }
+
+    fn remove_order(&self, order: &LimitOrder<Self::Api>) {
+        self.orders(order.id).clear();
+        self.user_orders(&order.owner).swap_remove(&order.id);
+        self.remove_escrow(&order.token_in, &order.amount_in);
+    }
+
+    #[view(getOrders)]
+    fn get_orders(&self, address: ManagedAddress) -> ManagedVec<LimitOrder<Self::Api>> {
+        let mut orders = ManagedVec::new();
+        for id in self.user_orders(&address).iter() {
+            orders.push(self.orders(id).get());
+        }
+
+        orders
+    }
+
+    #[view(canExecuteOrder)]
+    fn can_execute_order(&self, order_id: u64) -> bool {
+        if self.orders(order_id).is_empty() {
+            return false;
+        }
+
+        let order = self.orders(order_id).get();
+        let pair = self.pairs(order.pair_id).get();
+
+        pair.state == PairState::Active && self.is_order_executable(&pair, &order)
+    }
+
+    #[view(getOrder)]
+    #[storage_mapper("orders")]
+    fn orders(&self, id: u64) -> SingleValueMapper<LimitOrder<Self::Api>>;
+
+    #[storage_mapper("user_orders")]
+    fn user_orders(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
+
+    #[view(getLastOrderId)]
+    #[storage_mapper("last_order_id")]
+    fn last_order_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getOrderKeeperFee)]
+    #[storage_mapper("order_keeper_fee")]
+    fn order_keeper_fee(&self) -> SingleValueMapper<u64>;
+
+    #[only_owner]
+    #[endpoint(setOrderKeeperFee)]
+    fn set_order_keeper_fee(&self, fee: u64) {
+        require!(fee <= MAX_KEEPER_FEE, ERROR_WRONG_FEE);
+
+        self.order_keeper_fee().set(fee);
+        self.order_keeper_fee_changed_event(fee);
+    }
+}
@@ -1,39 +1,96 @@
-use crate::common::{self, config::*, errors::*};
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
 
 multiversx_sc::imports!();
 
 #[multiversx_sc::module]
 pub trait LiquidityModule:
 common::config::ConfigModule
++common::events::EventsModule
 +super::helpers::HelpersModule
++super::oracle::OracleModule
 {
     #[endpoint(addLiquidity)]
     #[payable("*")]
-    fn add_liquidity(&self) {
+    #[allow_multiple_var_args]
+    fn add_liquidity(
+        &self,
+        min_token_amount: OptionalValue<BigUint>,
+        min_base_amount: OptionalValue<BigUint>,
+        min_lp_out: OptionalValue<BigUint>,
+        deadline: OptionalValue<u64>,
+    ) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+        self.require_deadline(deadline);
 
-        let payments = self.call_value().all_esdt_transfers();
+        let payments = self.get_multi_payments();
         require!(payments.len() == 2, ERROR_WRONG_PAYMENT);
 
         let mut pair = match self.get_pair_by_tickers(&payments.get(0).token_identifier, &payments.get(1).token_identifier) {
             Option::Some(pair) => pair,
             Option::None => sc_panic!(ERROR_PAIR_NOT_FOUND),
         };
-        require!(pair.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        require!(pair.state == PairState::Active || pair.state == PairState::ActiveNoSwap, ERROR_PAIR_NOT_ACTIVE);
+        require!(self.lp_roles_state(pair.id).get() == LpRolesState::Granted, ERROR_LP_ROLES_NOT_GRANTED);
 
         if pair.lp_supply == 0 {
             self.only_owner_or_launchpad();
         }
 
         let caller = self.blockchain().get_caller();
-        let (mut base_amount, mut token_amount) = if payments.get(0).token_identifier == pair.token {
-            (payments.get(1).amount, payments.get(0).amount)
-        } else {
+        let (token_amount, base_amount) = if payments.get(0).token_identifier == pair.token {
             (payments.get(0).amount, payments.get(1).amount)
+        } else {
+            (payments.get(1).amount, payments.get(0).amount)
         };
+        let (token_amount, base_amount, lp_token_amount) = self.add_pair_liquidity(&mut pair, &caller, token_amount, base_amount);
+        require!(token_amount >= min_token_amount.into_option().unwrap_or_default(), ERROR_INSUFFICIENT_TOKEN_AMOUNT);
+        require!(base_amount >= min_base_amount.into_option().unwrap_or_default(), ERROR_INSUFFICIENT_BASE_AMOUNT);
+        require!(lp_token_amount >= min_lp_out.into_option().unwrap_or_default(), ERROR_INSUFFICIENT_LP_AMOUNT);
+    }
+
+    // deposits the amounts at the current pair ratio, refunds the surplus and sends the minted LP tokens to the caller.
+    // returns token amount added, base amount added, lp amount
+    fn add_pair_liquidity(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        caller: &ManagedAddress,
+        token_amount: BigUint,
+        base_amount: BigUint,
+    ) -> (BigUint, BigUint, BigUint) {
+        let (token_amount, base_amount, lp_token_amount) = self.mint_pair_liquidity(pair, caller, token_amount, base_amount);
+        self.send().direct_esdt(caller, &pair.lp_token, 0, &lp_token_amount);
+
+        (token_amount, base_amount, lp_token_amount)
+    }
+
+    // same as add_pair_liquidity, but the minted LP tokens stay in the contract
+    fn mint_pair_liquidity(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        caller: &ManagedAddress,
+        mut token_amount: BigUint,
+        mut base_amount: BigUint,
+    ) -> (BigUint, BigUint, BigUint) {
+        let mut locked_lp_amount = BigUint::zero();
         let lp_token_amount = if pair.lp_supply == BigUint::zero() {
-            base_amount.clone()
+            // the first MINIMUM_LIQUIDITY LP tokens stay in the contract forever, so the supply never goes back to zero
+            let initial_lp = match self.pair_curve(pair.id).get() {
+                CurveType::ConstantProduct => (&token_amount * &base_amount).sqrt(),
+                CurveType::StableSwap => {
+                    let (token_multiplier, base_multiplier) = self.get_precision_multipliers(pair.id);
+
+                    self.get_stable_invariant(self.get_pair_amp(pair.id), &(&token_amount * token_multiplier), &(&base_amount * base_multiplier))
+                },
+                // the value of the deposit in base tokens, as the base side is weight_base of the pool
+                CurveType::Weighted => &base_amount * MAX_PERCENT / self.get_stored_weights(pair.id).1,
+            };
+            require!(initial_lp > MINIMUM_LIQUIDITY, ERROR_INSUFFICIENT_INITIAL_LIQUIDITY);
+
+            locked_lp_amount = BigUint::from(MINIMUM_LIQUIDITY);
+            initial_lp - &locked_lp_amount
         } else {
+            // deposits at the reserve ratio keep the price for every curve type, weighted pools included
             let base_optimal = self.quote(&token_amount, &pair.liquidity_token, &pair.liquidity_base);
             let (token_added, base_added) = if base_optimal < base_amount {
                 (token_amount.clone(), base_optimal)
@@ -44,11 +101,11 @@ common::config::ConfigModule
             };
             // return surplus tokens
             if token_added < token_amount {
-                self.send().direct_esdt(&caller, &pair.token, 0, &(&token_amount - &token_added));
+                self.send_payout(caller, &pair.token, &(&token_amount - &token_added));
                 token_amount = token_added;
             }
             if base_added < base_amount {
-                self.send().direct_esdt(&caller, &pair.base_token, 0, &(&base_amount - &base_added));
+                self.send_payout(caller, &pair.base_token, &(&base_amount - &base_added));
                 base_amount = base_added;
             }
 
@@ -57,42 +114,90 @@ common::config::ConfigModule
 
             core::cmp::min(first_potential_lp, second_potential_lp)
         };
+        self.update_price_cumulatives(pair);
+        self.refresh_lbp_weights(pair);
         pair.liquidity_base += &base_amount;
         pair.liquidity_token += &token_amount;
-        pair.lp_supply += &lp_token_amount;
-        self.pairs(pair.id).set(&pair);
+        pair.lp_supply += &lp_token_amount + &locked_lp_amount;
+        self.pairs(pair.id).set(&*pair);
+
+        self.send().esdt_local_mint(&pair.lp_token, 0, &(&lp_token_amount + &locked_lp_amount));
 
-        self.send().esdt_local_mint(&pair.lp_token, 0, &lp_token_amount);
-        self.send().direct_esdt(&caller, &pair.lp_token, 0, &lp_token_amount);
+        self.add_liquidity_event(pair.id, caller, &LiquidityEvent {
+            token_amount: token_amount.clone(),
+            base_amount: base_amount.clone(),
+            lp_amount: lp_token_amount.clone(),
+            lp_supply: pair.lp_supply.clone(),
+            liquidity_token: pair.liquidity_token.clone(),
+            liquidity_base: pair.liquidity_base.clone(),
+        });
+
+        (token_amount, base_amount, lp_token_amount)
     }
 
     #[endpoint(removeLiquidity)]
     #[payable("*")]
-    fn remove_liquidity(&self) {
+    #[allow_multiple_var_args]
+    fn remove_liquidity(
+        &self,
+        min_token_amount: OptionalValue<BigUint>,
+        min_base_amount: OptionalValue<BigUint>,
+        deadline: OptionalValue<u64>,
+    ) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+        self.require_deadline(deadline);
 
         let payment = self.call_value().single_esdt();
         let mut pair = match self.get_pair_by_lp_token(&payment.token_identifier) {
             Option::Some(pair) => pair,
             Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
         };
-        require!(pair.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        require!(pair.state == PairState::Active || pair.state == PairState::ActiveNoSwap, ERROR_PAIR_NOT_ACTIVE);
+        require!(self.lp_roles_state(pair.id).get() == LpRolesState::Granted, ERROR_LP_ROLES_NOT_GRANTED);
 
         let caller = self.blockchain().get_caller();
-        let lp_token_amount = payment.amount;
-        let base_amount = &pair.liquidity_base * &lp_token_amount / &pair.lp_supply;
-        let token_amount = &pair.liquidity_token * &lp_token_amount / &pair.lp_supply;
+        let (token_amount, base_amount) = self.remove_pair_liquidity(&mut pair, &caller, &payment.amount);
+        require!(token_amount >= min_token_amount.into_option().unwrap_or_default(), ERROR_INSUFFICIENT_TOKEN_AMOUNT);
+        require!(base_amount >= min_base_amount.into_option().unwrap_or_default(), ERROR_INSUFFICIENT_BASE_AMOUNT);
+
+        self.send_payout(&caller, &pair.base_token, &base_amount);
+        self.send_payout(&caller, &pair.token, &token_amount);
+    }
+
+    // burns the LP tokens received by the contract and takes their share out of the reserves, without sending it.
+    // returns token amount, base amount
+    fn remove_pair_liquidity(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        caller: &ManagedAddress,
+        lp_token_amount: &BigUint,
+    ) -> (BigUint, BigUint) {
+        let base_amount = &pair.liquidity_base * lp_token_amount / &pair.lp_supply;
+        let token_amount = &pair.liquidity_token * lp_token_amount / &pair.lp_supply;
 
+        self.update_price_cumulatives(pair);
+        self.refresh_lbp_weights(pair);
         pair.liquidity_base -= &base_amount;
         pair.liquidity_token -= &token_amount;
-        pair.lp_supply -= &lp_token_amount;
-        if pair.lp_supply == 0 {
+        pair.lp_supply -= lp_token_amount;
+        if pair.lp_supply == 0 && pair.state != PairState::ActiveNoSwap {
             pair.state = PairState::ActiveNoSwap;
+            self.pair_state_changed_event(pair.id, pair.state);
         }
-        self.pairs(pair.id).set(&pair);
+        self.pairs(pair.id).set(&*pair);
+
+        self.send().esdt_local_burn(&pair.lp_token, 0, lp_token_amount);
+
+        self.remove_liquidity_event(pair.id, caller, &LiquidityEvent {
+            token_amount: token_amount.clone(),
+            base_amount: base_amount.clone(),
+            lp_amount: lp_token_amount.clone(),
+            lp_supply: pair.lp_supply.clone(),
+            liquidity_token: pair.liquidity_token.clone(),
+            liquidity_base: pair.liquidity_base.clone(),
+        });
 
-        self.send().esdt_local_burn(&pair.lp_token, 0, &lp_token_amount);
-        self.send().direct_esdt(&caller, &pair.base_token, 0, &base_amount);
-        self.send().direct_esdt(&caller, &pair.token, 0, &token_amount);
+        (token_amount, base_amount)
     }
 }
@@ -0,0 +1,159 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::common::{config::{self, CurveType, Pair}, consts::*, errors::*, events};
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct PriceObservation<M: ManagedTypeApi> {
+    pub timestamp: u64,
+    pub round: u64,
+    pub token_price_cumulative: BigUint<M>,
+    pub base_price_cumulative: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait OracleModule:
+config::ConfigModule
++events::EventsModule
++super::helpers::HelpersModule
+{
+    // must be called with the reserves in effect since the last update, before they are changed
+    fn update_price_cumulatives(&self, pair: &Pair<Self::Api>) {
+        let current = self.get_current_price_cumulatives(pair);
+        self.price_cumulatives(pair.id).set(&current);
+
+        let observations = self.price_observations(pair.id);
+        if !observations.is_empty() {
+            let last_index = self.last_price_observation_index(pair.id).get();
+            let last = observations.get(last_index);
+            if current.timestamp < last.timestamp + PRICE_OBSERVATION_PERIOD {
+                return;
+            }
+        }
+        self.push_price_observation(pair.id, current);
+    }
+
+    // ring buffer of observations, overwriting the oldest one once full
+    fn push_price_observation(&self, pair_id: usize, observation: PriceObservation<Self::Api>) {
+        let mut observations = self.price_observations(pair_id);
+        let index = if observations.len() < PRICE_OBSERVATIONS_CAPACITY {
+            observations.push(&observation)
+        } else {
+            let index = self.last_price_observation_index(pair_id).get() % PRICE_OBSERVATIONS_CAPACITY + 1;
+            observations.set(index, &observation);
+
+            index
+        };
+        self.last_price_observation_index(pair_id).set(index);
+    }
+
+    // cumulatives extrapolated to the current block using the stored reserves
+    fn get_current_price_cumulatives(&self, pair: &Pair<Self::Api>) -> PriceObservation<Self::Api> {
+        let timestamp = self.blockchain().get_block_timestamp();
+        let round = self.blockchain().get_block_round();
+        if self.price_cumulatives(pair.id).is_empty() {
+            return PriceObservation {
+                timestamp,
+                round,
+                token_price_cumulative: BigUint::zero(),
+                base_price_cumulative: BigUint::zero(),
+            };
+        }
+
+        let mut cumulatives = self.price_cumulatives(pair.id).get();
+        let elapsed = timestamp - cumulatives.timestamp;
+        if elapsed > 0 && pair.liquidity_token > 0 && pair.liquidity_base > 0 {
+            let (token_price, base_price) = self.get_spot_prices(pair);
+            cumulatives.token_price_cumulative += token_price * elapsed;
+            cumulatives.base_price_cumulative += base_price * elapsed;
+        }
+        cumulatives.timestamp = timestamp;
+        cumulatives.round = round;
+
+        cumulatives
+    }
+
+    // marginal prices of the pair's curve at the stored reserves: token in base and base in token, both scaled by
+    // PRICE_PRECISION
+    fn get_spot_prices(&self, pair: &Pair<Self::Api>) -> (BigUint, BigUint) {
+        let precision = BigUint::from(PRICE_PRECISION);
+        let token = &pair.liquidity_token;
+        let base = &pair.liquidity_base;
+        if self.pair_curve(pair.id).get() == CurveType::StableSwap {
+            // -dy/dx of 4A(x + y) + D = 4AD + D^3 / 4xy at a constant D, multiplied through by 4x^2y^2:
+            // (16A x^2 y^2 + D^3 y) / (16A x^2 y^2 + D^3 x), x being the scaled token reserve and y the scaled
+            // base one. the multipliers bring the price back to raw amounts
+            let amp = self.get_pair_amp(pair.id);
+            let (token_multiplier, base_multiplier) = self.get_precision_multipliers(pair.id);
+            let token = token * token_multiplier;
+            let base = base * base_multiplier;
+            let invariant = self.get_stable_invariant(amp, &token, &base);
+            let invariant_cubed = &invariant * &invariant * &invariant;
+            let amp_term = &token * &token * &base * &base * (amp * 16);
+            let token_term = (&invariant_cubed * &token + &amp_term) * base_multiplier;
+            let base_term = (amp_term + invariant_cubed * base) * token_multiplier;
+
+            return (&base_term * &precision / &token_term, token_term * precision / base_term);
+        }
+
+        // (base / weight_base) / (token / weight_token) and its inverse, constant product pairs being 50/50
+        let (weight_token, weight_base) = self.get_stored_weights(pair.id);
+        let weighted_token = token * weight_base;
+        let weighted_base = base * weight_token;
+
+        (&weighted_base * &precision / &weighted_token, weighted_token * precision / weighted_base)
+    }
+
+    #[view(getPriceCumulatives)]
+    fn get_price_cumulatives(&self, pair_id: usize) -> PriceObservation<Self::Api> {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        self.get_current_price_cumulatives(&self.pairs(pair_id).get())
+    }
+
+    // returns the average token price (in base) and base price (in token) over at least the last `window` seconds,
+    // both scaled by PRICE_PRECISION
+    #[view(getTwap)]
+    fn get_twap(&self, pair_id: usize, window: u64) -> MultiValue2<BigUint, BigUint> {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(window > 0, ERROR_WRONG_TWAP_WINDOW);
+
+        let current = self.get_current_price_cumulatives(&self.pairs(pair_id).get());
+        require!(current.timestamp >= window, ERROR_NOT_ENOUGH_OBSERVATIONS);
+
+        let target_timestamp = current.timestamp - window;
+        let mut start: Option<PriceObservation<Self::Api>> = None;
+        for observation in self.price_observations(pair_id).iter() {
+            if observation.timestamp > target_timestamp {
+                continue;
+            }
+            if let Some(found) = &start {
+                if found.timestamp >= observation.timestamp {
+                    continue;
+                }
+            }
+            start = Some(observation);
+        }
+        let start = match start {
+            Some(observation) => observation,
+            None => sc_panic!(ERROR_NOT_ENOUGH_OBSERVATIONS),
+        };
+
+        let elapsed = current.timestamp - start.timestamp;
+        let token_price = (current.token_price_cumulative - start.token_price_cumulative) / elapsed;
+        let base_price = (current.base_price_cumulative - start.base_price_cumulative) / elapsed;
+
+        (token_price, base_price).into()
+    }
+
+    #[storage_mapper("price_cumulatives")]
+    fn price_cumulatives(&self, pair_id: usize) -> SingleValueMapper<PriceObservation<Self::Api>>;
+
+    #[view(getPriceObservations)]
+    #[storage_mapper("price_observations")]
+    fn price_observations(&self, pair_id: usize) -> VecMapper<PriceObservation<Self::Api>>;
+
+    #[storage_mapper("last_price_observation_index")]
+    fn last_price_observation_index(&self, pair_id: usize) -> SingleValueMapper<usize>;
+}
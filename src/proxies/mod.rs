@@ -1 +1,2 @@
 pub mod launchpad_proxy;
+pub mod wrapper_proxy;
@@ -1,11 +1,13 @@
-use crate::common::{self, config::*, errors::*};
+use crate::common::{self, config::*, consts::*, errors::*, events::*};
 
 multiversx_sc::imports!();
 
 #[multiversx_sc::module]
 pub trait SwapModule:
 common::config::ConfigModule
++common::events::EventsModule
 +super::helpers::HelpersModule
++super::oracle::OracleModule
 {
     #[payable("*")]
     #[endpoint(swapFixedInput)]
@@ -13,34 +15,25 @@ common::config::ConfigModule
         &self,
         token_out: TokenIdentifier,
         min_amount_out: BigUint,
+        deadline: OptionalValue<u64>,
     ) {
-        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        let amount_out = self.swap_payment_fixed_input(&token_out, &min_amount_out, deadline);
 
-        let payment = self.call_value().single_esdt();
-        let mut pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
-            Some(pair) => pair,
-            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
-        };
-        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
-
-        let fee_in = self.base_tokens().contains(&payment.token_identifier);
-        let (amount_out, new_token_liquidity, new_base_liquidity, owner_fee) =
-            if token_out == pair.base_token {
-                self.do_swap_fixed_input(&payment.amount, &pair.liquidity_token, &pair.liquidity_base, fee_in)
-            } else {
-                let (amount_out, new_base_liquidity, new_token_liquidity, owner_fee) =
-                    self.do_swap_fixed_input(&payment.amount, &pair.liquidity_base, &pair.liquidity_token, fee_in);
-
-                (amount_out, new_token_liquidity, new_base_liquidity, owner_fee)
-            };
-        require!(amount_out >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+        self.send_payout(&self.blockchain().get_caller(), &token_out, &amount_out);
+    }
 
-        self.cummulated_fees().insert(pair.base_token.clone(), self.cummulated_fees().get(&pair.base_token).unwrap_or(BigUint::zero()) + owner_fee);
-        pair.liquidity_token = new_token_liquidity;
-        pair.liquidity_base = new_base_liquidity;
-        self.pairs(pair.id).set(&pair);
+    #[payable("*")]
+    #[endpoint(swapFixedInputTo)]
+    fn swap_fixed_input_to(
+        &self,
+        to: ManagedAddress,
+        token_out: TokenIdentifier,
+        min_amount_out: BigUint,
+        deadline: OptionalValue<u64>,
+    ) {
+        let amount_out = self.swap_payment_fixed_input(&token_out, &min_amount_out, deadline);
 
-        self.send().direct_esdt(&self.blockchain().get_caller(), &token_out, 0, &amount_out);
+        self.send_payout(&to, &token_out, &amount_out);
     }
 
     #[payable("*")]
@@ -49,89 +42,454 @@ common::config::ConfigModule
         &self,
         token_out: TokenIdentifier,
         amount_out_wanted: BigUint,
+        deadline: OptionalValue<u64>,
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.swap_payment_fixed_output(&caller, &token_out, &amount_out_wanted, deadline);
+
+        self.send_payout(&caller, &token_out, &amount_out_wanted);
+    }
+
+    // the output goes to `to`, while the unused input is refunded to the caller
+    #[payable("*")]
+    #[endpoint(swapFixedOutputTo)]
+    fn swap_fixed_output_to(
+        &self,
+        to: ManagedAddress,
+        token_out: TokenIdentifier,
+        amount_out_wanted: BigUint,
+        deadline: OptionalValue<u64>,
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.swap_payment_fixed_output(&caller, &token_out, &amount_out_wanted, deadline);
+
+        self.send_payout(&to, &token_out, &amount_out_wanted);
+    }
+
+    // swaps the payment and forwards the output (WEGLD is not unwrapped) to `endpoint` of the destination contract.
+    // the swap stays done if the destination call fails: the output comes back and is refunded to the caller,
+    // unwrapped like any other payout
+    #[payable("*")]
+    #[endpoint(swapFixedInputAndCall)]
+    fn swap_fixed_input_and_call(
+        &self,
+        token_out: TokenIdentifier,
+        min_amount_out: BigUint,
+        destination: ManagedAddress,
+        endpoint: ManagedBuffer,
+        args: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        require!(self.blockchain().is_smart_contract(&destination), ERROR_WRONG_ADDRESS);
+
+        let amount_out = self.swap_payment_fixed_input(&token_out, &min_amount_out, OptionalValue::None);
+        let caller = self.blockchain().get_caller();
+        self.tx()
+            .to(&destination)
+            .raw_call(endpoint)
+            .arguments_raw(args.to_arg_buffer())
+            .single_esdt(&token_out, 0, &amount_out)
+            .callback(self.callbacks().swap_and_call_callback(&caller, &token_out, &amount_out))
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn swap_and_call_callback(
+        &self,
+        caller: &ManagedAddress,
+        token_out: &TokenIdentifier,
+        amount_out: &BigUint,
+        #[call_result] result: ManagedAsyncCallResult<MultiValueEncoded<ManagedBuffer>>,
+    ) {
+        if let ManagedAsyncCallResult::Err(_) = result {
+            self.send_payout(caller, token_out, amount_out);
+        }
+    }
+
+    // swaps the single payment of the call. returns amount out
+    fn swap_payment_fixed_input(
+        &self,
+        token_out: &TokenIdentifier,
+        min_amount_out: &BigUint,
+        deadline: OptionalValue<u64>,
+    ) -> BigUint {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_deadline(deadline);
+
+        let payment = self.get_single_payment();
+        let mut pair = self.get_active_pair(&payment.token_identifier, token_out);
+
+        let amount_out = self.swap_pair_fixed_input(&mut pair, &payment.token_identifier, &payment.amount);
+        require!(&amount_out >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+        amount_out
+    }
+
+    // swaps the single payment of the call for exactly amount_out_wanted and refunds the rest of the payment
+    fn swap_payment_fixed_output(
+        &self,
+        refund_to: &ManagedAddress,
+        token_out: &TokenIdentifier,
+        amount_out_wanted: &BigUint,
+        deadline: OptionalValue<u64>,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_deadline(deadline);
+
+        let payment = self.get_single_payment();
+        let mut pair = self.get_active_pair(&payment.token_identifier, token_out);
+
+        let amount_in = self.swap_pair_fixed_output(&mut pair, &payment.token_identifier, amount_out_wanted);
+        require!(amount_in > BigUint::zero() && amount_in <= payment.amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
+
+        if amount_in < payment.amount {
+            self.send_payout(refund_to, &payment.token_identifier, &(payment.amount - amount_in));
+        }
+    }
+
+    // the path is a single list argument so the deadline can follow it, like on the other swap endpoints. EGLD is
+    // paid with a path starting at WEGLD
+    #[payable("*")]
+    #[endpoint(swapMultiHop)]
+    fn swap_multi_hop(
+        &self,
+        min_amount_out: BigUint,
+        path: ManagedVec<TokenIdentifier>,
+        deadline: OptionalValue<u64>,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_deadline(deadline);
+
+        let payment = self.get_single_payment();
+        self.require_valid_path(&path);
+        require!(path.get(0).clone_value() == payment.token_identifier, ERROR_INVALID_PATH);
+
+        let mut amount = payment.amount;
+        for i in 0..path.len() - 1 {
+            let mut pair = self.get_active_pair(&path.get(i), &path.get(i + 1));
+            amount = self.swap_pair_fixed_input(&mut pair, &path.get(i), &amount);
+        }
+        require!(amount >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+        let token_out = path.get(path.len() - 1).clone_value();
+        self.send_payout(&self.blockchain().get_caller(), &token_out, &amount);
+    }
+
+    #[payable("*")]
+    #[endpoint(swapMultiHopFixedOutput)]
+    fn swap_multi_hop_fixed_output(
+        &self,
+        amount_out_wanted: BigUint,
+        path: ManagedVec<TokenIdentifier>,
+        deadline: OptionalValue<u64>,
     ) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_deadline(deadline);
+
+        let payment = self.get_single_payment();
+        self.require_valid_path(&path);
+        require!(path.get(0).clone_value() == payment.token_identifier, ERROR_INVALID_PATH);
+
+        // walk the path backwards: each leg must output exactly what the next one needs
+        let mut amount = amount_out_wanted.clone();
+        for i in (0..path.len() - 1).rev() {
+            let mut pair = self.get_active_pair(&path.get(i), &path.get(i + 1));
+            amount = self.swap_pair_fixed_output(&mut pair, &path.get(i), &amount);
+        }
+        require!(amount > BigUint::zero() && amount <= payment.amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
 
-        let payment = self.call_value().single_esdt();
-        let mut pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
+        let caller = self.blockchain().get_caller();
+        let token_out = path.get(path.len() - 1).clone_value();
+        self.send_payout(&caller, &token_out, &amount_out_wanted);
+        if amount < payment.amount {
+            self.send_payout(&caller, &payment.token_identifier, &(payment.amount - amount));
+        }
+    }
+
+    // sends amount_out to the caller and calls callback_endpoint on it. the caller has to send back, in the same
+    // call, enough of the pair's tokens for the constant product (after fees) to be restored
+    #[endpoint(flashSwap)]
+    fn flash_swap(
+        &self,
+        pair_id: usize,
+        token_out: TokenIdentifier,
+        amount_out: BigUint,
+        callback_endpoint: ManagedBuffer,
+        args: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pairs(pair_id).get();
+        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+        require!(token_out == pair.token || token_out == pair.base_token, ERROR_WRONG_PAYMENT);
+        require!(amount_out > 0, ERROR_ZERO_AMOUNT);
+        let (token_out_amount, base_out_amount) = if token_out == pair.token {
+            require!(amount_out < pair.liquidity_token, ERROR_NO_LIQUIDITY);
+            (amount_out.clone(), BigUint::zero())
+        } else {
+            require!(amount_out < pair.liquidity_base, ERROR_NO_LIQUIDITY);
+            (BigUint::zero(), amount_out.clone())
+        };
+
+        let caller = self.blockchain().get_caller();
+        self.lock();
+        let back_transfers = self.tx()
+            .to(&caller)
+            .raw_call(callback_endpoint)
+            .arguments_raw(args.to_arg_buffer())
+            .single_esdt(&token_out, 0, &amount_out)
+            .returns(ReturnsBackTransfers)
+            .sync_call();
+        self.unlock();
+
+        require!(back_transfers.total_egld_amount == 0, ERROR_WRONG_PAYMENT);
+        let mut token_in_amount = BigUint::zero();
+        let mut base_in_amount = BigUint::zero();
+        for payment in back_transfers.esdt_payments.iter() {
+            if payment.token_identifier == pair.token {
+                token_in_amount += &payment.amount;
+            } else if payment.token_identifier == pair.base_token {
+                base_in_amount += &payment.amount;
+            } else {
+                sc_panic!(ERROR_WRONG_PAYMENT);
+            }
+        }
+        require!(
+            &pair.liquidity_token + &token_in_amount > token_out_amount && &pair.liquidity_base + &base_in_amount > base_out_amount,
+            ERROR_FLASH_SWAP_NOT_REPAID
+        );
+
+        // same check as uniswap v2: the reserves minus the fee on whatever came in must keep the pair invariant
+        let (lp_fee, owner_fee) = self.get_pair_fee_rates(pair.id);
+        let total_fee = lp_fee + owner_fee;
+        let new_token_liquidity = &pair.liquidity_token + &token_in_amount - &token_out_amount;
+        let new_base_liquidity = &pair.liquidity_base + &base_in_amount - &base_out_amount;
+        let adjusted_token = &new_token_liquidity * MAX_PERCENT - &token_in_amount * total_fee;
+        let adjusted_base = &new_base_liquidity * MAX_PERCENT - &base_in_amount * total_fee;
+        let scaled_token = &pair.liquidity_token * MAX_PERCENT;
+        let scaled_base = &pair.liquidity_base * MAX_PERCENT;
+        require!(
+            self.is_invariant_kept(&pair, &adjusted_token, &adjusted_base, &scaled_token, &scaled_base),
+            ERROR_FLASH_SWAP_NOT_REPAID
+        );
+
+        let owner_fee_token = &token_in_amount * owner_fee / MAX_PERCENT;
+        let owner_fee_base = &base_in_amount * owner_fee / MAX_PERCENT;
+        self.add_cummulated_fee(&pair.token, &owner_fee_token);
+        self.add_cummulated_fee(&pair.base_token, &owner_fee_base);
+
+        self.update_price_cumulatives(&pair);
+        self.refresh_lbp_weights(&pair);
+        pair.liquidity_token = new_token_liquidity - owner_fee_token;
+        pair.liquidity_base = new_base_liquidity - owner_fee_base;
+        self.pairs(pair.id).set(&pair);
+
+        self.flash_swap_event(pair.id, &caller, &token_out, &FlashSwapEvent {
+            amount_out,
+            token_repaid: token_in_amount,
+            base_repaid: base_in_amount,
+            liquidity_token: pair.liquidity_token,
+            liquidity_base: pair.liquidity_base,
+        });
+    }
+
+    fn get_active_pair(&self, token_in: &TokenIdentifier, token_out: &TokenIdentifier) -> Pair<Self::Api> {
+        let pair = match self.get_pair_by_tickers(token_in, token_out) {
             Some(pair) => pair,
             None => sc_panic!(ERROR_PAIR_NOT_FOUND),
         };
         require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
 
-        let fee_in = self.base_tokens().contains(&payment.token_identifier);
-        let (amount_in, new_token_liquidity, new_base_liquidity, owner_fee) =
-            if token_out == pair.base_token {
-                self.do_swap_fixed_output(&amount_out_wanted, &pair.liquidity_token, &pair.liquidity_base, fee_in)
+        pair
+    }
+
+    // a path must have at least two tokens and must not repeat any of them, so no pair is used twice
+    fn require_valid_path(&self, path: &ManagedVec<TokenIdentifier>) {
+        require!(path.len() >= 2, ERROR_INVALID_PATH);
+        for i in 0..path.len() - 1 {
+            for j in i + 1..path.len() {
+                require!(path.get(i).clone_value() != path.get(j).clone_value(), ERROR_INVALID_PATH);
+            }
+        }
+    }
+
+    // swaps amount_in of token_in against the pair and stores the new reserves. returns amount out
+    fn swap_pair_fixed_input(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+    ) -> BigUint {
+        self.require_not_locked();
+        self.update_price_cumulatives(pair);
+        self.refresh_lbp_weights(pair);
+        let (amount_out, new_token_liquidity, new_base_liquidity, lp_fee, owner_fee) =
+            self.get_swap_fixed_input_result(pair, token_in, amount_in);
+
+        let fee_token = self.get_fee_token(pair, token_in);
+        self.add_cummulated_fee(&fee_token, &owner_fee);
+        pair.liquidity_token = new_token_liquidity;
+        pair.liquidity_base = new_base_liquidity;
+        self.pairs(pair.id).set(&*pair);
+        self.emit_swap_event(pair, token_in, amount_in, &amount_out, fee_token, lp_fee, owner_fee);
+
+        amount_out
+    }
+
+    // returns amount out, new token liquidity, new base liquidity, lp fee, owner fee without touching the storage
+    fn get_swap_fixed_input_result(
+        &self,
+        pair: &Pair<Self::Api>,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+    ) -> (BigUint, BigUint, BigUint, BigUint, BigUint) {
+        let fee_in = self.base_tokens().contains(token_in);
+        let weights = self.get_swap_weights(pair, token_in);
+        let multipliers = self.get_swap_multipliers(pair, token_in);
+        if token_in == &pair.token {
+            self.do_swap_fixed_input(pair.id, amount_in, &pair.liquidity_token, &pair.liquidity_base, fee_in, weights, multipliers)
+        } else {
+            let (amount_out, new_base_liquidity, new_token_liquidity, lp_fee, owner_fee) =
+                self.do_swap_fixed_input(pair.id, amount_in, &pair.liquidity_base, &pair.liquidity_token, fee_in, weights, multipliers);
+
+            (amount_out, new_token_liquidity, new_base_liquidity, lp_fee, owner_fee)
+        }
+    }
+
+    // what swap_pair_fixed_input would pay out, None when the pair can't take amount_in
+    fn try_get_pair_amount_out(
+        &self,
+        pair: &Pair<Self::Api>,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+    ) -> Option<BigUint> {
+        let fee_in = self.base_tokens().contains(token_in);
+        let weights = self.get_swap_weights(pair, token_in);
+        let multipliers = self.get_swap_multipliers(pair, token_in);
+        if token_in == &pair.token {
+            self.try_get_amount_out(pair.id, amount_in, &pair.liquidity_token, &pair.liquidity_base, fee_in, weights, multipliers)
+        } else {
+            self.try_get_amount_out(pair.id, amount_in, &pair.liquidity_base, &pair.liquidity_token, fee_in, weights, multipliers)
+        }
+    }
+
+    // swaps token_in for exactly amount_out against the pair and stores the new reserves. returns amount in
+    fn swap_pair_fixed_output(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        token_in: &TokenIdentifier,
+        amount_out: &BigUint,
+    ) -> BigUint {
+        self.require_not_locked();
+        self.update_price_cumulatives(pair);
+        self.refresh_lbp_weights(pair);
+        let fee_in = self.base_tokens().contains(token_in);
+        let weights = self.get_swap_weights(pair, token_in);
+        let multipliers = self.get_swap_multipliers(pair, token_in);
+        let (amount_in, new_token_liquidity, new_base_liquidity, lp_fee, owner_fee) =
+            if token_in == &pair.token {
+                self.do_swap_fixed_output(pair.id, amount_out, &pair.liquidity_token, &pair.liquidity_base, fee_in, weights, multipliers)
             } else {
-                let (amount_in, new_base_liquidity, new_token_liquidity, owner_fee) =
-                    self.do_swap_fixed_output(&amount_out_wanted, &pair.liquidity_base, &pair.liquidity_token, fee_in);
+                let (amount_in, new_base_liquidity, new_token_liquidity, lp_fee, owner_fee) =
+                    self.do_swap_fixed_output(pair.id, amount_out, &pair.liquidity_base, &pair.liquidity_token, fee_in, weights, multipliers);
 
-                (amount_in, new_token_liquidity, new_base_liquidity, owner_fee)
+                (amount_in, new_token_liquidity, new_base_liquidity, lp_fee, owner_fee)
             };
-        require!(amount_in > BigUint::zero() && amount_in <= payment.amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
 
-        self.cummulated_fees().insert(pair.base_token.clone(), self.cummulated_fees().get(&pair.base_token).unwrap_or(BigUint::zero()) + owner_fee);
+        let fee_token = self.get_fee_token(pair, token_in);
+        self.add_cummulated_fee(&fee_token, &owner_fee);
         pair.liquidity_token = new_token_liquidity;
         pair.liquidity_base = new_base_liquidity;
-        self.pairs(pair.id).set(&pair);
+        self.pairs(pair.id).set(&*pair);
+        self.emit_swap_event(pair, token_in, &amount_in, amount_out, fee_token, lp_fee, owner_fee);
 
-        let caller = self.blockchain().get_caller();
-        self.send().direct_esdt(&caller, &token_out, 0, &amount_out_wanted);
-        if amount_in < payment.amount {
-            self.send().direct_esdt(&caller, &payment.token_identifier, 0, &(payment.amount - amount_in));
-        }
+        amount_in
+    }
+
+    fn emit_swap_event(
+        &self,
+        pair: &Pair<Self::Api>,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        amount_out: &BigUint,
+        fee_token: TokenIdentifier,
+        lp_fee: BigUint,
+        owner_fee: BigUint,
+    ) {
+        let token_out = if token_in == &pair.token { &pair.base_token } else { &pair.token };
+        self.swap_event(
+            pair.id,
+            &self.blockchain().get_caller(),
+            token_in,
+            token_out,
+            &SwapEvent {
+                amount_in: amount_in.clone(),
+                amount_out: amount_out.clone(),
+                fee_token,
+                lp_fee,
+                owner_fee,
+                liquidity_token: pair.liquidity_token.clone(),
+                liquidity_base: pair.liquidity_base.clone(),
+            },
+        );
     }
 
     fn do_swap_fixed_input(
         &self,
+        pair_id: usize,
         amount_in: &BigUint,
         liquidity_in: &BigUint,
         liquidity_out: &BigUint,
         fee_in: bool,
-    ) -> (BigUint, BigUint, BigUint, BigUint) {
+        weights: (u64, u64),
+        multipliers: (u64, u64),
+    ) -> (BigUint, BigUint, BigUint, BigUint, BigUint) {
         if fee_in {
-            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(amount_in, true);
+            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(pair_id, amount_in, true);
             let left_amount_in = amount_in - &total_fee;
-            let amount_out = self.get_amount_out_no_fee(&left_amount_in, liquidity_in, liquidity_out);
-            let new_liquidity_in = liquidity_in + &left_amount_in + lp_fee;
+            let amount_out = self.get_amount_out_no_fee(pair_id, &left_amount_in, liquidity_in, liquidity_out, weights, multipliers);
+            let new_liquidity_in = liquidity_in + &left_amount_in + &lp_fee;
             let new_liquidity_out = liquidity_out - &amount_out;
 
-            (amount_out, new_liquidity_in, new_liquidity_out, owner_fee)
+            (amount_out, new_liquidity_in, new_liquidity_out, lp_fee, owner_fee)
         } else {
-            let amount_out = self.get_amount_out_no_fee(amount_in, liquidity_in, liquidity_out);
-            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(&amount_out, true);
+            let amount_out = self.get_amount_out_no_fee(pair_id, amount_in, liquidity_in, liquidity_out, weights, multipliers);
+            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(pair_id, &amount_out, true);
             let left_amount_out = &amount_out - &total_fee;
             let new_liquidity_in = liquidity_in + amount_in;
-            let new_liquidity_out = liquidity_out - &amount_out + lp_fee;
+            let new_liquidity_out = liquidity_out - &amount_out + &lp_fee;
 
-            (left_amount_out, new_liquidity_in, new_liquidity_out, owner_fee)
+            (left_amount_out, new_liquidity_in, new_liquidity_out, lp_fee, owner_fee)
         }
     }
 
     fn do_swap_fixed_output(
         &self,
+        pair_id: usize,
         amount_out: &BigUint,
         liquidity_in: &BigUint,
         liquidity_out: &BigUint,
         fee_in: bool,
-    ) -> (BigUint, BigUint, BigUint, BigUint) {
+        weights: (u64, u64),
+        multipliers: (u64, u64),
+    ) -> (BigUint, BigUint, BigUint, BigUint, BigUint) {
         if fee_in {
-            let amount_in_no_fee = self.get_amount_in_no_fee(amount_out, liquidity_in, liquidity_out);
-            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(&amount_in_no_fee, false);
+            let amount_in_no_fee = self.get_amount_in_no_fee(pair_id, amount_out, liquidity_in, liquidity_out, weights, multipliers);
+            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(pair_id, &amount_in_no_fee, false);
             let amount_in = &amount_in_no_fee + &total_fee;
-            let new_liquidity_in = liquidity_in + &amount_in_no_fee + lp_fee;
+            let new_liquidity_in = liquidity_in + &amount_in_no_fee + &lp_fee;
             let new_liquidity_out = liquidity_out - amount_out;
 
-            (amount_in, new_liquidity_in, new_liquidity_out, owner_fee)
+            (amount_in, new_liquidity_in, new_liquidity_out, lp_fee, owner_fee)
         } else {
-            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(amount_out, false);
+            let (lp_fee, owner_fee, total_fee) = self.get_fee_amounts(pair_id, amount_out, false);
             let left_amount_out = amount_out + &total_fee;
-            let amount_in = self.get_amount_in_no_fee(&left_amount_out, liquidity_in, liquidity_out);
+            let amount_in = self.get_amount_in_no_fee(pair_id, &left_amount_out, liquidity_in, liquidity_out, weights, multipliers);
             let new_liquidity_in = liquidity_in + &amount_in;
-            let new_liquidity_out = liquidity_out - &left_amount_out + lp_fee;
+            let new_liquidity_out = liquidity_out - &left_amount_out + &lp_fee;
 
-            (amount_in, new_liquidity_in, new_liquidity_out, owner_fee)
+            (amount_in, new_liquidity_in, new_liquidity_out, lp_fee, owner_fee)
         }
     }
 
@@ -149,14 +507,16 @@ common::config::ConfigModule
             None => sc_panic!(ERROR_PAIR_NOT_FOUND),
         };
         let fee_in = self.base_tokens().contains(token_in);
+        let weights = self.get_swap_weights(&pair, token_in);
+        let multipliers = self.get_swap_multipliers(&pair, token_in);
         if token_in == &pair.token {
             require!(pair.liquidity_base > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_out(&amount_in, &pair.liquidity_token, &pair.liquidity_base, fee_in)
+            self.get_amount_out(pair.id, &amount_in, &pair.liquidity_token, &pair.liquidity_base, fee_in, weights, multipliers)
         } else {
             require!(pair.liquidity_token > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_out(&amount_in, &pair.liquidity_base, &pair.liquidity_token, fee_in)
+            self.get_amount_out(pair.id, &amount_in, &pair.liquidity_base, &pair.liquidity_token, fee_in, weights, multipliers)
         }
     }
 
@@ -174,14 +534,109 @@ common::config::ConfigModule
             None => sc_panic!(ERROR_PAIR_NOT_FOUND),
         };
         let fee_in = self.base_tokens().contains(token_in);
+        let weights = self.get_swap_weights(&pair, token_in);
+        let multipliers = self.get_swap_multipliers(&pair, token_in);
         if token_in == &pair.token {
             require!(pair.liquidity_base > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_in(&amount_out, &pair.liquidity_token, &pair.liquidity_base, fee_in)
+            self.get_amount_in(pair.id, &amount_out, &pair.liquidity_token, &pair.liquidity_base, fee_in, weights, multipliers)
         } else {
             require!(pair.liquidity_token > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_in(&amount_out, &pair.liquidity_base, &pair.liquidity_token, fee_in)
+            self.get_amount_in(pair.id, &amount_out, &pair.liquidity_base, &pair.liquidity_token, fee_in, weights, multipliers)
+        }
+    }
+
+    #[view(getAmountOutPath)]
+    fn get_amount_out_path_view(
+        &self,
+        amount_in: BigUint,
+        path: MultiValueEncoded<TokenIdentifier>,
+    ) -> BigUint {
+        let path = path.to_vec();
+        self.require_valid_path(&path);
+
+        let mut amount = amount_in;
+        for i in 0..path.len() - 1 {
+            amount = self.get_amount_out_view(&path.get(i), &path.get(i + 1), amount);
+        }
+
+        amount
+    }
+
+    #[view(getAmountInPath)]
+    fn get_amount_in_path_view(
+        &self,
+        amount_out: BigUint,
+        path: MultiValueEncoded<TokenIdentifier>,
+    ) -> BigUint {
+        let path = path.to_vec();
+        self.require_valid_path(&path);
+
+        let mut amount = amount_out;
+        for i in (0..path.len() - 1).rev() {
+            amount = self.get_amount_in_view(&path.get(i), &path.get(i + 1), amount);
+        }
+
+        amount
+    }
+
+    #[view(getBestRoute)]
+    fn get_best_route(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        amount_in: BigUint,
+        max_hops: usize,
+    ) -> MultiValue2<ManagedVec<TokenIdentifier>, BigUint> {
+        require!(amount_in > 0, ERROR_ZERO_AMOUNT);
+        require!(token_in != token_out, ERROR_INVALID_PATH);
+        require!(max_hops > 0 && max_hops <= MAX_ROUTE_HOPS, ERROR_WRONG_MAX_HOPS);
+
+        let mut path = ManagedVec::new();
+        path.push(token_in.clone());
+        let mut best = (ManagedVec::new(), BigUint::zero());
+        self.find_best_route(&token_out, &amount_in, max_hops, &mut path, &mut best);
+        require!(best.1 > 0, ERROR_ROUTE_NOT_FOUND);
+
+        best.into()
+    }
+
+    // depth first search over the pair graph, only following the pairs indexed for the current token and quoting
+    // every leg exactly like swap_pair_fixed_input would execute it
+    fn find_best_route(
+        &self,
+        token_out: &TokenIdentifier,
+        amount_in: &BigUint,
+        hops_left: usize,
+        path: &mut ManagedVec<TokenIdentifier>,
+        best: &mut (ManagedVec<TokenIdentifier>, BigUint),
+    ) {
+        let token_in = path.get(path.len() - 1).clone_value();
+        for (next_token, pair_id) in self.token_pairs(&token_in).iter() {
+            if path.iter().any(|token| *token == next_token) {
+                continue;
+            }
+
+            let pair = self.pairs(pair_id).get();
+            if pair.state != PairState::Active || pair.liquidity_token == 0 || pair.liquidity_base == 0 {
+                continue;
+            }
+
+            let amount_out = match self.try_get_pair_amount_out(&pair, &token_in, amount_in) {
+                Some(amount_out) if amount_out > 0 => amount_out,
+                _ => continue,
+            };
+
+            path.push(next_token.clone());
+            if &next_token == token_out {
+                if amount_out > best.1 {
+                    *best = (path.clone(), amount_out);
+                }
+            } else if hops_left > 1 {
+                self.find_best_route(token_out, &amount_out, hops_left - 1, path, best);
+            }
+            path.remove(path.len() - 1);
         }
     }
 }
@@ -0,0 +1,177 @@
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ZapModule:
+common::config::ConfigModule
++common::events::EventsModule
++super::helpers::HelpersModule
++super::oracle::OracleModule
++super::liquidity::LiquidityModule
++super::swap::SwapModule
+{
+    // adds liquidity from a single token: swaps the part of the payment that leaves the rest matching the new
+    // pair ratio, then deposits both sides
+    #[payable("*")]
+    #[endpoint(zapIn)]
+    fn zap_in(&self, pair_id: usize, min_lp_out: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let payment = self.get_single_payment();
+        let mut pair = self.get_zap_pair(pair_id, &payment.token_identifier);
+
+        let (token_amount, base_amount) = self.swap_zap_amounts(&mut pair, &payment.token_identifier, &payment.amount);
+        let caller = self.blockchain().get_caller();
+        let (_, _, lp_amount) = self.add_pair_liquidity(&mut pair, &caller, token_amount, base_amount);
+        require!(lp_amount >= min_lp_out, ERROR_INSUFFICIENT_LP_AMOUNT);
+    }
+
+    // swaps the right part of amount_in against the pair and returns the token and base amounts to deposit
+    fn swap_zap_amounts(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+    ) -> (BigUint, BigUint) {
+        let swap_amount = self.get_zap_swap_amount(pair, token_in, amount_in);
+        let amount_out = self.swap_pair_fixed_input(pair, token_in, &swap_amount);
+        let amount_left = amount_in - &swap_amount;
+        if token_in == &pair.token {
+            (amount_left, amount_out)
+        } else {
+            (amount_out, amount_left)
+        }
+    }
+
+    #[view(getZapInQuote)]
+    fn get_zap_in_quote(&self, pair_id: usize, token_in: TokenIdentifier, amount_in: BigUint) -> BigUint {
+        let pair = self.get_zap_pair(pair_id, &token_in);
+
+        let swap_amount = self.get_zap_swap_amount(&pair, &token_in, &amount_in);
+        let (amount_out, new_token_liquidity, new_base_liquidity, _, _) =
+            self.get_swap_fixed_input_result(&pair, &token_in, &swap_amount);
+        let amount_left = &amount_in - &swap_amount;
+        let (token_amount, base_amount) = if token_in == pair.token {
+            (amount_left, amount_out)
+        } else {
+            (amount_out, amount_left)
+        };
+
+        core::cmp::min(
+            token_amount * &pair.lp_supply / new_token_liquidity,
+            base_amount * &pair.lp_supply / new_base_liquidity,
+        )
+    }
+
+    // removes liquidity into a single token: the other side is swapped against the reserves left after the removal
+    #[payable("*")]
+    #[endpoint(zapOut)]
+    fn zap_out(&self, token_wanted: TokenIdentifier, min_amount_out: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.require_not_locked();
+
+        let payment = self.call_value().single_esdt();
+        let mut pair = match self.get_pair_by_lp_token(&payment.token_identifier) {
+            Option::Some(pair) => pair,
+            Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
+        };
+        self.get_zap_pair(pair.id, &token_wanted);
+
+        let caller = self.blockchain().get_caller();
+        let (token_amount, base_amount) = self.remove_pair_liquidity(&mut pair, &caller, &payment.amount);
+        require!(pair.state == PairState::Active, ERROR_NO_LIQUIDITY);
+
+        let amount_out = if token_wanted == pair.token {
+            let base_token = pair.base_token.clone();
+            token_amount + self.swap_pair_fixed_input(&mut pair, &base_token, &base_amount)
+        } else {
+            let token = pair.token.clone();
+            base_amount + self.swap_pair_fixed_input(&mut pair, &token, &token_amount)
+        };
+        require!(amount_out >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+        self.send_payout(&caller, &token_wanted, &amount_out);
+    }
+
+    #[view(getZapOutQuote)]
+    fn get_zap_out_quote(&self, pair_id: usize, token_wanted: TokenIdentifier, lp_amount: BigUint) -> BigUint {
+        let mut pair = self.get_zap_pair(pair_id, &token_wanted);
+        require!(lp_amount > 0 && lp_amount < pair.lp_supply, ERROR_NO_LIQUIDITY);
+
+        let token_amount = &pair.liquidity_token * &lp_amount / &pair.lp_supply;
+        let base_amount = &pair.liquidity_base * &lp_amount / &pair.lp_supply;
+        pair.liquidity_token -= &token_amount;
+        pair.liquidity_base -= &base_amount;
+        if token_wanted == pair.token {
+            let (amount_out, _, _, _, _) = self.get_swap_fixed_input_result(&pair, &pair.base_token, &base_amount);
+
+            token_amount + amount_out
+        } else {
+            let (amount_out, _, _, _, _) = self.get_swap_fixed_input_result(&pair, &pair.token, &token_amount);
+
+            base_amount + amount_out
+        }
+    }
+
+    fn get_zap_pair(&self, pair_id: usize, token_in: &TokenIdentifier) -> Pair<Self::Api> {
+        require!(!self.pairs(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pairs(pair_id).get();
+        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+        require!(self.lp_roles_state(pair_id).get() == LpRolesState::Granted, ERROR_LP_ROLES_NOT_GRANTED);
+        require!(token_in == &pair.token || token_in == &pair.base_token, ERROR_WRONG_PAYMENT);
+        require!(pair.lp_supply > 0, ERROR_NO_LIQUIDITY);
+
+        pair
+    }
+
+    // part of amount_in to swap so that what is left and what comes out are in the post-swap reserve ratio.
+    // for constant product pairs, with r the input reserve and f, l, o the total, lp and owner fee rates it solves a * s^2 + b * s - c = 0 where
+    //   fee on input:  a = (1 - f) * (1 - o), b = r * (2 - f),         c = r * amount_in
+    //   fee on output: a = 1 - f + l,         b = r * (2 - f) - l * amount_in, c = r * amount_in
+    // all scaled by MAX_PERCENT^2, as s = 2c / (b + sqrt(b^2 + 4ac)) so a negative b needs no signed math
+    fn get_zap_swap_amount(&self, pair: &Pair<Self::Api>, token_in: &TokenIdentifier, amount_in: &BigUint) -> BigUint {
+        require!(amount_in > &0, ERROR_ZERO_AMOUNT);
+
+        let (reserve_in, reserve_out) = if token_in == &pair.token {
+            (&pair.liquidity_token, &pair.liquidity_base)
+        } else {
+            (&pair.liquidity_base, &pair.liquidity_token)
+        };
+        let curve = self.pair_curve(pair.id).get();
+        if curve == CurveType::StableSwap {
+            // close to the peg the swap is nearly 1:1 once scaled, so (amount_in - s) / (reserve_in + s) = s / (reserve_out - s)
+            let (multiplier_in, multiplier_out) = self.get_swap_multipliers(pair, token_in);
+            let scaled_reserve_out = reserve_out * multiplier_out;
+            return amount_in * &scaled_reserve_out / (&(amount_in + reserve_in) * multiplier_in + scaled_reserve_out);
+        }
+        if curve == CurveType::Weighted {
+            // the share of the value held on the other side, leaving the price impact to the refund
+            let (_, weight_out) = self.get_swap_weights(pair, token_in);
+            return amount_in * weight_out / MAX_PERCENT;
+        }
+
+        let (lp_fee, owner_fee) = self.get_pair_fee_rates(pair.id);
+        let total_fee = lp_fee + owner_fee;
+        let c = reserve_in * amount_in * MAX_PERCENT * MAX_PERCENT;
+        let reserve_term = reserve_in * (2 * MAX_PERCENT - total_fee) * MAX_PERCENT;
+        let (a, fee_term) = if self.base_tokens().contains(token_in) {
+            (BigUint::from(MAX_PERCENT - total_fee) * (MAX_PERCENT - owner_fee), BigUint::zero())
+        } else {
+            (BigUint::from(MAX_PERCENT - total_fee + lp_fee) * MAX_PERCENT, amount_in * lp_fee * MAX_PERCENT)
+        };
+        let four_ac = &a * &c * 4u64;
+        let denominator = if reserve_term >= fee_term {
+            let b = reserve_term - fee_term;
+
+            (&b * &b + four_ac).sqrt() + b
+        } else {
+            let minus_b = fee_term - reserve_term;
+
+            (&minus_b * &minus_b + four_ac).sqrt() - minus_b
+        };
+
+        c * 2u64 / denominator
+    }
+}
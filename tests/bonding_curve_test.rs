@@ -0,0 +1,245 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::bonding_curve::BondingCurveModule;
+use tfn_dex::common::config::*;
+use tfn_dex::liquidity::LiquidityModule;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+const PRICE_ONE: u64 = 1_000_000_000_000_000_000;
+const SALE_SUPPLY: u64 = 1_000_000;
+const RESERVED_TOKENS: u64 = 200_000;
+const TARGET_BASE: u64 = 300_000;
+// the linear slope is scaled twice by the price precision: 0.000001 BASE more per token sold
+const LINEAR_SLOPE: u128 = PRICE_ONE as u128 * PRICE_ONE as u128 / 1_000_000;
+
+// pair 0 selling 1_000_000 TOKEN from 0.1 BASE, the price rising by 0.000001 BASE per token sold
+fn setup_curve(curve_type: BondingCurveType, price_param: u128) -> ScenarioWorld {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, SALE_SUPPLY + RESERVED_TOKENS))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.start_bonding_curve(
+                0,
+                curve_type,
+                BigUint::from(PRICE_ONE / 10),
+                BigUint::from(price_param),
+                BigUint::from(RESERVED_TOKENS),
+                BigUint::from(TARGET_BASE),
+            );
+        });
+
+    world
+}
+
+fn buy(world: &mut ScenarioWorld, base_amount: u64) -> u64 {
+    let mut tokens_sold_before = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        tokens_sold_before = sc.bonding_curves(0).get().tokens_sold.to_u64().unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, base_amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.buy_bonding_curve(0, BigUint::zero());
+        });
+
+    let mut tokens_sold_after = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        tokens_sold_after = sc.bonding_curves(0).get().tokens_sold.to_u64().unwrap();
+    });
+
+    tokens_sold_after - tokens_sold_before
+}
+
+#[test]
+fn bonding_curve_price_rises_with_buys() {
+    let mut world = setup_curve(BondingCurveType::Linear, LINEAR_SLOPE);
+    let first = buy(&mut world, 10_000);
+    let second = buy(&mut world, 10_000);
+
+    // 9_700 BASE after fees buy 97_000 tokens at 0.1 BASE, fewer as the price moves up to ~0.17
+    assert!(first < 97_000 && first > 60_000);
+    assert!(second < first);
+}
+
+#[test]
+fn bonding_curve_sell_returns_less_than_paid() {
+    let mut world = setup_curve(BondingCurveType::Exponential, SALE_SUPPLY as u128 / 2);
+    let tokens = buy(&mut world, 10_000);
+    let mut quote = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        quote = sc.get_bonding_curve_sell_quote(0, BigUint::from(tokens)).to_u64().unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, tokens))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.sell_bonding_curve(0, BigUint::from(quote));
+        });
+
+    assert!(quote < 10_000 && quote > 9_000);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let curve = sc.bonding_curves(0).get();
+        assert_eq!(curve.tokens_sold, 0u64);
+        // the LP part of both fees stays raised for the pool
+        assert!(curve.base_raised > 0u64);
+    });
+}
+
+#[test]
+fn bonding_curve_graduates_into_a_locked_pool() {
+    let mut world = setup_curve(BondingCurveType::Linear, LINEAR_SLOPE);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, TARGET_BASE + 100_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.buy_bonding_curve(0, BigUint::zero());
+        });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(0).get();
+        assert_eq!(pair.state, PairState::Active);
+        assert_eq!(pair.liquidity_token, RESERVED_TOKENS);
+        // everything raised except the owner fee seeds the pool
+        assert_eq!(pair.liquidity_base, (TARGET_BASE + 100_000) * (10_000 - OWNER_FEE) / 10_000);
+        assert_eq!(pair.lp_supply, (&pair.liquidity_token * &pair.liquidity_base).sqrt());
+        assert!(sc.bonding_curves(0).is_empty());
+    });
+    // unsold tokens went back to the creator, only the reserved ones are left in the pool
+    world
+        .check_account(DEX)
+        .esdt_balance(TOKEN, RESERVED_TOKENS)
+        .esdt_balance(LP_TOKEN, 0u64);
+
+    // the graduated pair trades as a regular constant product pool
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+}
+
+#[test]
+fn bonding_curve_pair_refuses_liquidity_and_state_changes() {
+    let mut world = setup_curve(BondingCurveType::Linear, LINEAR_SLOPE);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .returns(ExpectError(4, "pair not active"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "pair in bonding curve"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.set_pair_inactive(0);
+        });
+}
+
+#[test]
+fn bonding_curve_holdings_are_reconciled() {
+    let mut world = setup_curve(BondingCurveType::Linear, LINEAR_SLOPE);
+    buy(&mut world, 10_000);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(BigInt::from(fees), surplus);
+        }
+    });
+}
+
+#[test]
+fn bonding_curve_sells_back_everything_bought_in_steps() {
+    for (curve_type, price_param) in [
+        (BondingCurveType::Linear, LINEAR_SLOPE),
+        (BondingCurveType::Exponential, SALE_SUPPLY as u128 / 2),
+    ] {
+        let mut world = setup_curve(curve_type, price_param);
+        // without fees nothing covers the rounding of each step
+        world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+            sc.set_pair_fees(0, 0, 0);
+        });
+        let mut tokens = 0;
+        for step in 0..30 {
+            tokens += buy(&mut world, 1_001 + step * 37);
+        }
+        world
+            .tx()
+            .from(USER)
+            .to(DEX)
+            .esdt(TestEsdtTransfer(TOKEN, 0, tokens))
+            .whitebox(tfn_dex::contract_obj, |sc| {
+                sc.sell_bonding_curve(0, BigUint::zero());
+            });
+
+        // the sale never pays out more than was raised, what is left staying raised
+        let mut base_raised = 0;
+        world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+            let curve = sc.bonding_curves(0).get();
+            assert_eq!(curve.tokens_sold, 0u64);
+            base_raised = curve.base_raised.to_u64().unwrap();
+        });
+        assert!(base_raised < 30);
+        world.check_account(USER)
+            .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - base_raised)
+            .esdt_balance(TOKEN, INITIAL_BALANCE);
+    }
+}
+
+#[test]
+fn bonding_curve_sells_are_capped_to_the_base_raised() {
+    let mut world = setup_curve(BondingCurveType::Linear, LINEAR_SLOPE);
+    let tokens = buy(&mut world, 10_000);
+    // rounding left the curve one short of its integral
+    let mut base_raised = 0;
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let mut curve = sc.bonding_curves(0).get();
+        curve.base_raised = sc.get_bonding_curve_cost(&curve, &BigUint::zero(), &curve.tokens_sold) - 1u64;
+        base_raised = curve.base_raised.to_u64().unwrap();
+        sc.bonding_curves(0).set(&curve);
+    });
+
+    let mut quote = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        quote = sc.get_bonding_curve_sell_quote(0, BigUint::from(tokens)).to_u64().unwrap();
+    });
+    assert_eq!(quote, base_raised - base_raised * (LP_FEE + OWNER_FEE) / 10_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, tokens))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.sell_bonding_curve(0, BigUint::from(quote));
+        });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let curve = sc.bonding_curves(0).get();
+        assert_eq!(curve.tokens_sold, 0u64);
+        // only the LP part of the fee is left
+        assert_eq!(curve.base_raised, base_raised * LP_FEE / 10_000);
+    });
+}
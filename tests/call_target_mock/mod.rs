@@ -0,0 +1,27 @@
+#![allow(dead_code)]
+
+multiversx_sc::imports!();
+
+// destination of swapFixedInputAndCall: keeps what it's sent on `deposit`, refuses it on `reject`
+#[multiversx_sc::contract]
+pub trait CallTargetMock {
+    #[init]
+    fn init(&self) {}
+
+    #[payable("*")]
+    #[endpoint(deposit)]
+    fn deposit(&self, depositor: ManagedAddress) {
+        let payment = self.call_value().single_esdt();
+        self.deposits(&depositor).update(|amount| *amount += payment.amount);
+    }
+
+    #[payable("*")]
+    #[endpoint(reject)]
+    fn reject(&self) {
+        sc_panic!("rejected");
+    }
+
+    #[view(getDeposits)]
+    #[storage_mapper("deposits")]
+    fn deposits(&self, depositor: &ManagedAddress) -> SingleValueMapper<BigUint>;
+}
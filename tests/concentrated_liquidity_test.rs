@@ -0,0 +1,266 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::concentrated_liquidity::ConcentratedLiquidityModule;
+
+const PRICE_ONE: u64 = 1_000_000_000_000_000_000;
+
+// pool 0 of TOKEN/BASE at 1 BASE per TOKEN, ticks every 60
+fn setup_pool() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.create_cl_pool(BASE_TOKEN.to_token_identifier(), TOKEN.to_token_identifier(), 60, BigUint::from(PRICE_ONE));
+    });
+
+    world
+}
+
+fn add_position(world: &mut ScenarioWorld, from: TestAddress, ticks: (i64, i64), token_amount: u64, base_amount: u64) {
+    world
+        .tx()
+        .from(from)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, token_amount))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, base_amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_cl_position(0, ticks.0, ticks.1, BigUint::zero());
+        });
+}
+
+fn sell_tokens(world: &mut ScenarioWorld, amount: u64) {
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_cl(0, BigUint::zero());
+        });
+}
+
+fn get_pool(world: &mut ScenarioWorld) -> (i64, u64, u64, u64) {
+    let mut result = (0, 0, 0, 0);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pool = sc.cl_pools(0).get();
+        result = (
+            pool.current_tick,
+            pool.liquidity.to_u64().unwrap(),
+            pool.reserve_token.to_u64().unwrap(),
+            pool.reserve_base.to_u64().unwrap(),
+        );
+    });
+
+    result
+}
+
+#[test]
+fn cl_positions_take_amounts_matching_the_price() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-600, 600), 100_000, 100_000);
+    let (tick, liquidity, reserve_token, reserve_base) = get_pool(&mut world);
+    assert_eq!(tick, 0);
+    assert!(liquidity > 0);
+    // a symmetric range around the price takes both sides equally
+    assert_eq!(reserve_token, reserve_base);
+    assert!(reserve_token > 99_900);
+
+    // a range above the price is made of tokens only and does not add active liquidity
+    add_position(&mut world, OWNER, (1_200, 2_400), 50_000, 50_000);
+    let (_, new_liquidity, new_reserve_token, new_reserve_base) = get_pool(&mut world);
+    assert_eq!(new_liquidity, liquidity);
+    assert_eq!(new_reserve_base, reserve_base);
+    assert!(new_reserve_token > reserve_token + 49_900);
+    world
+        .check_account(OWNER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - reserve_base)
+        .esdt_nft_balance_and_attributes(CL_POSITION_TOKEN, 2, 1, None::<()>);
+}
+
+#[test]
+fn cl_swap_matches_quote_and_moves_the_price_down() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-6_000, 6_000), 1_000_000, 1_000_000);
+    let (_, _, reserve_token, reserve_base) = get_pool(&mut world);
+    let mut quote = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        quote = sc
+            .get_cl_amount_out(0, TOKEN.to_token_identifier(), BigUint::from(10_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    sell_tokens(&mut world, 10_000);
+
+    let (tick, _, new_reserve_token, new_reserve_base) = get_pool(&mut world);
+    assert_eq!(reserve_base - new_reserve_base, quote);
+    // the owner fee is the only part of the input not added to the reserves
+    assert_eq!(new_reserve_token - reserve_token, 10_000 - 10_000 * OWNER_FEE / 10_000);
+    assert!(tick < 0);
+    // concentrated liquidity gets closer to the price than the whole range would
+    assert!(quote > 9_600 && quote < 9_700);
+}
+
+#[test]
+fn cl_swap_crosses_ticks() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-6_000, 6_000), 1_000_000, 1_000_000);
+    let (_, wide_liquidity, _, _) = get_pool(&mut world);
+    add_position(&mut world, OWNER, (-600, 600), 100_000, 100_000);
+    sell_tokens(&mut world, 300_000);
+
+    let (tick, liquidity, _, _) = get_pool(&mut world);
+    assert!(tick < -600);
+    assert_eq!(liquidity, wide_liquidity);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let lower = sc.cl_ticks(0, -600).get();
+        let pool = sc.cl_pools(0).get();
+        // crossing flipped the fee growth outside, which started at zero, to the growth at that point
+        assert!(lower.fee_growth_outside_token > 0u64);
+        assert!(lower.fee_growth_outside_token < pool.fee_growth_token);
+    });
+}
+
+#[test]
+fn cl_position_removal_pays_liquidity_and_fees() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-6_000, 6_000), 1_000_000, 1_000_000);
+    sell_tokens(&mut world, 100_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 100_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_cl(0, BigUint::zero());
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(CL_POSITION_TOKEN, 1, 1))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            let position = sc.blockchain().get_token_attributes::<ClPosition<DebugApi>>(&CL_POSITION_TOKEN.to_token_identifier(), 1);
+            let (_, _, fees_token, fees_base) = sc.get_cl_position_amounts(position).into_tuple();
+            // 2% of each swap goes to the only position, less rounding
+            assert!(fees_token > 1_990u64 && fees_base > 1_990u64);
+
+            sc.remove_cl_position(BigUint::zero(), BigUint::zero());
+        });
+
+    // only rounding dust is left behind
+    let (_, liquidity, reserve_token, reserve_base) = get_pool(&mut world);
+    assert_eq!(liquidity, 0);
+    assert!(reserve_token < 10 && reserve_base < 10);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.cl_tick_bitmap(0, -2).is_empty() && sc.cl_tick_bitmap(0, 1).is_empty());
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(BigInt::from(fees), surplus);
+        }
+    });
+}
+
+#[test]
+fn cl_ticks_are_kept_in_a_bitmap() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-600, 600), 100_000, 100_000);
+    add_position(&mut world, USER, (-600, 1_200), 100_000, 100_000);
+
+    // ticks divided by the spacing of 60, 64 of them per word
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cl_tick_bitmap(0, -1).get(), 1u64 << 54);
+        assert_eq!(sc.cl_tick_bitmap(0, 0).get(), (1u64 << 10) | (1u64 << 20));
+    });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(CL_POSITION_TOKEN, 1, 1))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_cl_position(BigUint::zero(), BigUint::zero());
+        });
+
+    // -600 is still used by the other position
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cl_tick_bitmap(0, -1).get(), 1u64 << 54);
+        assert_eq!(sc.cl_tick_bitmap(0, 0).get(), 1u64 << 20);
+        assert!(sc.cl_ticks(0, 600).is_empty());
+    });
+}
+
+#[test]
+fn cl_swap_walks_across_empty_words() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-600, 600), 100_000, 100_000);
+    // far below the price, made of base only, six words away
+    add_position(&mut world, OWNER, (-30_000, -24_000), 0, 1_000_000);
+    let mut far_liquidity = 0;
+    let mut quote = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        far_liquidity = sc.cl_ticks(0, -24_000).get().liquidity_gross.to_u64().unwrap();
+        quote = sc
+            .get_cl_amount_out(0, TOKEN.to_token_identifier(), BigUint::from(300_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    let (_, _, _, reserve_base) = get_pool(&mut world);
+    sell_tokens(&mut world, 300_000);
+
+    let (tick, liquidity, _, new_reserve_base) = get_pool(&mut world);
+    assert!(tick < -24_000 && tick > -30_000);
+    assert_eq!(liquidity, far_liquidity);
+    assert_eq!(reserve_base - new_reserve_base, quote);
+    // ~100_000 from the first range, the rest at ~0.09 BASE per TOKEN
+    assert!(quote > 110_000 && quote < 120_000);
+}
+
+#[test]
+fn cl_swap_beyond_the_last_tick_fails() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-600, 600), 100_000, 100_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000_000))
+        .returns(ExpectError(4, "insufficient liquidity"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_cl(0, BigUint::zero());
+        });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000_000))
+        .returns(ExpectError(4, "insufficient liquidity"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_cl(0, BigUint::zero());
+        });
+}
+
+#[test]
+fn cl_positions_need_the_min_liquidity_of_the_pool() {
+    let mut world = setup_pool();
+    add_position(&mut world, OWNER, (-600, 600), 100_000, 100_000);
+    let (_, liquidity, _, _) = get_pool(&mut world);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_cl_min_position_liquidity(0, BigUint::from(liquidity * 2));
+    });
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 100_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 100_000))
+        .returns(ExpectError(4, "insufficient liquidity"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_cl_position(0, -600, 600, BigUint::zero());
+        });
+    add_position(&mut world, USER, (-600, 600), 200_000, 200_000);
+
+    let (_, new_liquidity, _, _) = get_pool(&mut world);
+    assert!(new_liquidity >= liquidity * 3);
+}
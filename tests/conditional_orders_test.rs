@@ -0,0 +1,161 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::conditional_orders::ConditionalOrdersModule;
+use tfn_dex::swap::SwapModule;
+
+const PRICE_ONE: u64 = 1_000_000_000_000_000_000;
+const START_TIME: u64 = 1_000;
+const TWAP_WINDOW: u64 = 600;
+const EXPIRES_AT: u64 = 100_000;
+
+// pair 0 TOKEN/BASE at 1 BASE per TOKEN, with USER placing an order selling 1_000 TOKEN
+fn setup_order(order_type: ConditionalOrderType, trigger_price: u64, max_slippage: u64) -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.current_block().block_timestamp(START_TIME);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.place_conditional_order(
+                BASE_TOKEN.to_token_identifier(),
+                order_type,
+                BigUint::from(trigger_price),
+                TWAP_WINDOW,
+                max_slippage,
+                EXPIRES_AT,
+            );
+        });
+
+    world
+}
+
+fn swap(world: &mut ScenarioWorld, token_in: TestTokenIdentifier, token_out: TestTokenIdentifier, amount: u64) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(token_in, 0, amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(token_out.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+}
+
+fn execute_order(world: &mut ScenarioWorld, expected_error: Option<&str>) {
+    let tx = world.tx().from(OWNER).to(DEX);
+    let whitebox = |sc: tfn_dex::ContractObj<DebugApi>| {
+        sc.execute_conditional_order(1);
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(tfn_dex::contract_obj, whitebox),
+        None => tx.whitebox(tfn_dex::contract_obj, whitebox),
+    };
+}
+
+#[test]
+fn stop_loss_triggers_on_the_average_price() {
+    let mut world = setup_order(ConditionalOrderType::StopLoss, PRICE_ONE / 10 * 9, 500);
+    // the dump moves the price to 0.64 for the whole window
+    swap(&mut world, TOKEN, BASE_TOKEN, 250_000);
+    world.current_block().block_timestamp(START_TIME + TWAP_WINDOW);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.is_conditional_order_triggered_view(1));
+    });
+    execute_order(&mut world, None);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.conditional_orders(1).is_empty());
+        assert!(sc.escrowed_tokens().is_empty());
+    });
+}
+
+#[test]
+fn take_profit_waits_for_the_average_to_reach_the_trigger() {
+    let mut world = setup_order(ConditionalOrderType::TakeProfit, PRICE_ONE / 10 * 14, 500);
+    world.current_block().block_timestamp(START_TIME + TWAP_WINDOW);
+    execute_order(&mut world, Some("order not triggered"));
+
+    // the pump to ~1.56 only lifts the average above 1.4 once it held for a whole window
+    swap(&mut world, BASE_TOKEN, TOKEN, 250_000);
+    world.current_block().block_timestamp(START_TIME + TWAP_WINDOW * 2 - 1);
+    execute_order(&mut world, Some("order not triggered"));
+    world.current_block().block_timestamp(START_TIME + TWAP_WINDOW * 2);
+    execute_order(&mut world, None);
+}
+
+#[test]
+fn execution_is_bounded_by_max_slippage() {
+    let mut world = setup_order(ConditionalOrderType::StopLoss, PRICE_ONE / 10 * 9, 0);
+    swap(&mut world, TOKEN, BASE_TOKEN, 250_000);
+    world.current_block().block_timestamp(START_TIME + TWAP_WINDOW);
+
+    // the swap fee alone puts the output below the average price
+    execute_order(&mut world, Some("max slippage exceeded"));
+}
+
+#[test]
+fn expired_orders_can_only_be_refunded() {
+    let mut world = setup_order(ConditionalOrderType::StopLoss, PRICE_ONE / 10 * 9, 500);
+    swap(&mut world, TOKEN, BASE_TOKEN, 250_000);
+    world.current_block().block_timestamp(EXPIRES_AT + 1);
+    execute_order(&mut world, Some("order expired"));
+
+    // anyone can send an expired order back to its owner
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.cancel_conditional_order(1);
+    });
+    world.check_account(USER).esdt_balance(TOKEN, INITIAL_BALANCE);
+}
+
+fn place_stop_loss(world: &mut ScenarioWorld, twap_window: u64, expected_error: Option<&str>) {
+    let tx = world.tx().from(USER).to(DEX).esdt(TestEsdtTransfer(TOKEN, 0, 1_000));
+    let whitebox = |sc: tfn_dex::ContractObj<DebugApi>| {
+        sc.place_conditional_order(
+            BASE_TOKEN.to_token_identifier(),
+            ConditionalOrderType::StopLoss,
+            BigUint::from(PRICE_ONE * 2),
+            twap_window,
+            500,
+            EXPIRES_AT,
+        );
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(tfn_dex::contract_obj, whitebox),
+        None => tx.whitebox(tfn_dex::contract_obj, whitebox),
+    };
+}
+
+#[test]
+fn twap_window_must_fit_the_observations() {
+    let mut world = setup_order(ConditionalOrderType::StopLoss, PRICE_ONE / 10 * 9, 500);
+    // 64 observations a minute apart reach back 63 minutes at most
+    place_stop_loss(&mut world, 59, Some("wrong twap window"));
+    place_stop_loss(&mut world, 3_781, Some("wrong twap window"));
+    place_stop_loss(&mut world, 3_780, None);
+
+    // a busy pair fills the ring and keeps overwriting it, one observation per period
+    for period in 1..=70 {
+        world.current_block().block_timestamp(START_TIME + period * 60);
+        if period % 2 == 0 {
+            swap(&mut world, TOKEN, BASE_TOKEN, 1_000);
+        } else {
+            swap(&mut world, BASE_TOKEN, TOKEN, 1_000);
+        }
+    }
+
+    // the newest observation being half a period old, the oldest one is still within the window
+    world.current_block().block_timestamp(START_TIME + 70 * 60 + 30);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.execute_conditional_order(2);
+    });
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.conditional_orders(2).is_empty());
+    });
+}
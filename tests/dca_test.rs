@@ -0,0 +1,117 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::dca::DcaModule;
+
+const PRICE_ONE: u64 = 1_000_000_000_000_000_000;
+const START_TIME: u64 = 1_000;
+const INTERVAL: u64 = 3_600;
+
+// USER buying TOKEN with 2_000 BASE on pair 0, 1_000 BASE every hour
+fn setup_dca(min_price: u64) -> ScenarioWorld {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+    world.current_block().block_timestamp(START_TIME);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 2_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.create_dca(TOKEN.to_token_identifier(), BigUint::from(1_000u64), INTERVAL, BigUint::from(min_price));
+        });
+
+    world
+}
+
+fn execute_dca(world: &mut ScenarioWorld) {
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.execute_dca(1);
+    });
+}
+
+#[test]
+fn dca_runs_once_per_interval() {
+    let mut world = setup_dca(0);
+    execute_dca(&mut world);
+    world.current_block().block_timestamp(START_TIME + INTERVAL - 1);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "dca interval not elapsed"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.execute_dca(1);
+        });
+    world.current_block().block_timestamp(START_TIME + INTERVAL);
+    execute_dca(&mut world);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let schedule = sc.dca_schedules(1).get();
+        assert_eq!(schedule.remaining, 0u64);
+        assert_eq!(schedule.last_execution, START_TIME + INTERVAL);
+        // ~970 TOKEN per 1_000 BASE after fees, a bit less for the second one
+        assert!(schedule.accumulated > 1_900u64 && schedule.accumulated < 1_940u64);
+    });
+}
+
+#[test]
+fn dca_refuses_prices_below_the_minimum() {
+    let mut world = setup_dca(2 * PRICE_ONE);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "price below the dca minimum"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.execute_dca(1);
+        });
+}
+
+#[test]
+fn claiming_a_finished_dca_removes_it() {
+    let mut world = setup_dca(0);
+    execute_dca(&mut world);
+    world.current_block().block_timestamp(START_TIME + INTERVAL);
+    execute_dca(&mut world);
+    let mut accumulated = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        accumulated = sc.dca_schedules(1).get().accumulated.to_u64().unwrap();
+    });
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.claim_dca(1);
+    });
+
+    world.check_account(USER).esdt_balance(TOKEN, INITIAL_BALANCE + accumulated);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.dca_schedules(1).is_empty());
+        assert!(sc.get_dca_schedules(USER.to_managed_address()).is_empty());
+        assert!(sc.escrowed_tokens().is_empty());
+    });
+}
+
+#[test]
+fn cancelling_a_dca_pays_out_both_sides() {
+    let mut world = setup_dca(0);
+    execute_dca(&mut world);
+    let mut accumulated = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        accumulated = sc.dca_schedules(1).get().accumulated.to_u64().unwrap();
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(BigInt::from(fees), surplus);
+        }
+    });
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.cancel_dca(1);
+    });
+
+    world
+        .check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 1_000)
+        .esdt_balance(TOKEN, INITIAL_BALANCE + accumulated);
+}
@@ -0,0 +1,135 @@
+#![allow(dead_code)]
+
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::liquidity::LiquidityModule;
+
+pub const OWNER: TestAddress = TestAddress::new("owner");
+pub const USER: TestAddress = TestAddress::new("user");
+pub const DEX: TestSCAddress = TestSCAddress::new("tfn-dex");
+pub const CODE_PATH: MxscPath = MxscPath::new("output/tfn-dex.mxsc.json");
+
+pub const BASE_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("BASE-123456");
+pub const OTHER_BASE_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("OBASE-123456");
+pub const TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKEN-123456");
+pub const LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKENBASE-123456");
+pub const BASE_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("OBASEBASE-123456");
+pub const WEGLD_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("WEGLD-123456");
+pub const WEGLD_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("WEGLDBASE-123456");
+pub const CL_POSITION_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TFNCLP-123456");
+
+pub const INITIAL_BALANCE: u64 = 1_000_000_000_000;
+pub const LP_FEE: u64 = 200;
+pub const OWNER_FEE: u64 = 100;
+
+pub fn world() -> ScenarioWorld {
+    let mut blockchain = ScenarioWorld::new();
+    blockchain.register_contract(CODE_PATH, tfn_dex::ContractBuilder);
+
+    blockchain
+}
+
+// the LP token issue goes through the system SC, so pairs are written straight into the storage
+pub fn setup_dex() -> ScenarioWorld {
+    let mut world = world();
+    let lp_roles = vec!["ESDTRoleLocalMint".to_string(), "ESDTRoleLocalBurn".to_string()];
+    for address in [OWNER, USER] {
+        world
+            .account(address)
+            .nonce(1)
+            .esdt_balance(BASE_TOKEN, INITIAL_BALANCE)
+            .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE)
+            .esdt_balance(TOKEN, INITIAL_BALANCE)
+            .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE)
+            .balance(INITIAL_BALANCE);
+    }
+    world
+        .account(DEX)
+        .nonce(1)
+        .code(CODE_PATH)
+        .owner(OWNER)
+        .esdt_roles(LP_TOKEN, lp_roles.clone())
+        .esdt_roles(BASE_LP_TOKEN, lp_roles.clone())
+        .esdt_roles(WEGLD_LP_TOKEN, lp_roles)
+        .esdt_roles(CL_POSITION_TOKEN, vec!["ESDTRoleNFTCreate".to_string(), "ESDTRoleNFTBurn".to_string()]);
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.state().set(State::Active);
+        sc.lp_fee().set(LP_FEE);
+        sc.owner_fee().set(OWNER_FEE);
+        sc.base_tokens().insert(BASE_TOKEN.to_token_identifier());
+        sc.base_tokens().insert(OTHER_BASE_TOKEN.to_token_identifier());
+        sc.cl_position_token().set(CL_POSITION_TOKEN.to_token_identifier());
+        create_test_pair(&sc, BASE_TOKEN, TOKEN, LP_TOKEN);
+        create_test_pair(&sc, BASE_TOKEN, OTHER_BASE_TOKEN, BASE_LP_TOKEN);
+        create_test_pair(&sc, BASE_TOKEN, WEGLD_TOKEN, WEGLD_LP_TOKEN);
+    });
+
+    world
+}
+
+// extra pair on top of the ones of setup_dex, returns its id
+pub fn add_test_pair(
+    world: &mut ScenarioWorld,
+    base_token: TestTokenIdentifier,
+    token: TestTokenIdentifier,
+    lp_token: TestTokenIdentifier,
+) -> usize {
+    world.set_esdt_local_roles(DEX, lp_token.as_bytes(), &[EsdtLocalRole::Mint, EsdtLocalRole::Burn]);
+    let mut id = 0;
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        id = sc.last_pair_id().get();
+        create_test_pair(&sc, base_token, token, lp_token);
+    });
+
+    id
+}
+
+fn create_test_pair(
+    sc: &tfn_dex::ContractObj<DebugApi>,
+    base_token: TestTokenIdentifier,
+    token: TestTokenIdentifier,
+    lp_token: TestTokenIdentifier,
+) {
+    let id = sc.last_pair_id().get();
+    let pair = Pair {
+        id,
+        state: PairState::ActiveNoSwap,
+        token: token.to_token_identifier(),
+        base_token: base_token.to_token_identifier(),
+        lp_token: lp_token.to_token_identifier(),
+        lp_supply: BigUint::zero(),
+        liquidity_token: BigUint::zero(),
+        liquidity_base: BigUint::zero(),
+    };
+    sc.last_pair_id().set(id + 1);
+    sc.pairs(id).set(&pair);
+    sc.index_pair(&pair);
+    sc.lp_roles_state(id).set(LpRolesState::Granted);
+}
+
+pub fn add_liquidity(
+    world: &mut ScenarioWorld,
+    from: TestAddress,
+    first: (TestTokenIdentifier, u64),
+    second: (TestTokenIdentifier, u64),
+) {
+    world
+        .tx()
+        .from(from)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(first.0, 0, first.1))
+        .esdt(TestEsdtTransfer(second.0, 0, second.1))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+}
+
+pub fn set_pair_active(world: &mut ScenarioWorld, pair_id: usize) {
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let mut pair = sc.pairs(pair_id).get();
+        pair.state = PairState::Active;
+        sc.pairs(pair_id).set(&pair);
+    });
+}
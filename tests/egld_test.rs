@@ -0,0 +1,237 @@
+mod dex_setup;
+mod wrapper_mock;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::liquidity::LiquidityModule;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+const WRAPPER: TestSCAddress = TestSCAddress::new("wrapper");
+const WRAPPER_CODE_PATH: MxscPath = MxscPath::new("output/wrapper-mock.mxsc.json");
+
+// pair 2 is BASE (base) / WEGLD (token)
+fn setup_egld_pair() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.register_contract(WRAPPER_CODE_PATH, wrapper_mock::ContractBuilder);
+    world
+        .account(WRAPPER)
+        .nonce(1)
+        .code(WRAPPER_CODE_PATH)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE)
+        .balance(INITIAL_BALANCE);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_egld_wrapper(WRAPPER.to_managed_address(), WEGLD_TOKEN.to_token_identifier());
+    });
+    add_liquidity(&mut world, OWNER, (WEGLD_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 2);
+
+    world
+}
+
+fn quote_amount_out(world: &mut ScenarioWorld, token_in: TestTokenIdentifier, token_out: TestTokenIdentifier, amount_in: u64) -> u64 {
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&token_in.to_token_identifier(), &token_out.to_token_identifier(), BigUint::from(amount_in))
+            .to_u64()
+            .unwrap();
+    });
+
+    amount_out
+}
+
+#[test]
+fn swap_fixed_input_accepts_egld() {
+    let mut world = setup_egld_pair();
+    let amount_out = quote_amount_out(&mut world, WEGLD_TOKEN, BASE_TOKEN, 10_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .egld(10_000)
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    world.check_account(USER)
+        .balance(INITIAL_BALANCE - 10_000)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE + amount_out)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE);
+    world.check_account(WRAPPER)
+        .balance(INITIAL_BALANCE + 10_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE - 10_000);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.pairs(2).get().liquidity_token, BigUint::from(1_000_000u64 + 10_000));
+    });
+}
+
+#[test]
+fn swap_fixed_input_pays_out_wegld_as_egld() {
+    let mut world = setup_egld_pair();
+    let amount_out = quote_amount_out(&mut world, BASE_TOKEN, WEGLD_TOKEN, 10_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(WEGLD_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    world.check_account(USER)
+        .balance(INITIAL_BALANCE + amount_out)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE);
+}
+
+#[test]
+fn swap_fixed_output_refunds_egld() {
+    let mut world = setup_egld_pair();
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_in = sc
+            .get_amount_in_view(&WEGLD_TOKEN.to_token_identifier(), &BASE_TOKEN.to_token_identifier(), BigUint::from(5_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .egld(10_000)
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output(BASE_TOKEN.to_token_identifier(), BigUint::from(5_000u64), OptionalValue::None);
+        });
+
+    world.check_account(USER)
+        .balance(INITIAL_BALANCE - amount_in)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE + 5_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE);
+}
+
+#[test]
+fn egld_payment_without_wrapper_fails() {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .egld(10_000)
+        .returns(ExpectError(4, "egld wrapper not set"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+}
+
+#[test]
+fn remove_liquidity_pays_out_wegld_as_egld() {
+    let mut world = setup_egld_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(WEGLD_LP_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+
+    world.check_account(OWNER)
+        .balance(INITIAL_BALANCE + 10_000)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 1_000_000 + 10_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE - 1_000_000);
+}
+
+#[test]
+fn withdrawn_wegld_fees_are_paid_out_as_egld() {
+    let mut world = setup_egld_pair();
+    // as a base token WEGLD pays the fee on the input side
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.add_base_token(WEGLD_TOKEN.to_token_identifier());
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .egld(10_000)
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cummulated_fees().get(&WEGLD_TOKEN.to_token_identifier()).unwrap(), 10_000 * OWNER_FEE / 10_000);
+        sc.withdraw_fees();
+        assert!(sc.cummulated_fees().is_empty());
+    });
+
+    world.check_account(OWNER)
+        .balance(INITIAL_BALANCE + 10_000 * OWNER_FEE / 10_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE - 1_000_000);
+}
+
+#[test]
+fn multi_hop_swaps_take_and_pay_out_egld() {
+    let mut world = setup_egld_pair();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+    let to_egld = [TOKEN, BASE_TOKEN, WEGLD_TOKEN];
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let path = to_egld.iter().map(|token| token.to_token_identifier()).collect();
+        amount_out = sc.get_amount_out_path_view(BigUint::from(10_000u64), path).to_u64().unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            let path = to_egld.iter().map(|token| token.to_token_identifier()).collect();
+            sc.swap_multi_hop(BigUint::zero(), path, OptionalValue::None);
+        });
+    world.check_account(USER)
+        .balance(INITIAL_BALANCE + amount_out)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE);
+
+    // paid in EGLD, the surplus comes back as EGLD too
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let path = to_egld.iter().rev().map(|token| token.to_token_identifier()).collect();
+        amount_in = sc.get_amount_in_path_view(BigUint::from(5_000u64), path).to_u64().unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .egld(10_000)
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            let path = to_egld.iter().rev().map(|token| token.to_token_identifier()).collect();
+            sc.swap_multi_hop_fixed_output(BigUint::from(5_000u64), path, OptionalValue::None);
+        });
+    world.check_account(USER)
+        .balance(INITIAL_BALANCE + amount_out - amount_in)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 10_000 + 5_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE);
+}
+
+#[test]
+fn failed_swap_and_call_refunds_wegld_as_egld() {
+    let mut world = setup_egld_pair();
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.swap_and_call_callback(
+            &USER.to_managed_address(),
+            &WEGLD_TOKEN.to_token_identifier(),
+            &BigUint::from(5_000u64),
+            ManagedAsyncCallResult::Err(ManagedAsyncCallError {
+                err_code: 10,
+                err_msg: ManagedBuffer::from("call failed"),
+            }),
+        );
+    });
+
+    world.check_account(USER)
+        .balance(INITIAL_BALANCE + 5_000)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE);
+}
@@ -0,0 +1,149 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::farming::FarmingModule;
+
+const START_TIME: u64 = 1_000;
+const END_TIME: u64 = 2_000;
+const REWARDS: u64 = 100_000;
+
+// pair 0 at 1 BASE per TOKEN, paying 100 TOKEN per second to its stakers
+fn setup_farm() -> ScenarioWorld {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    add_liquidity(&mut world, USER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+    world.current_block().block_timestamp(START_TIME);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, REWARDS))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.fund_farm(0, START_TIME, END_TIME);
+        });
+
+    world
+}
+
+fn stake(world: &mut ScenarioWorld, from: TestAddress, amount: u64) {
+    world
+        .tx()
+        .from(from)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.stake_lp();
+        });
+}
+
+fn pending_rewards(world: &mut ScenarioWorld, address: TestAddress) -> u64 {
+    let mut pending = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        pending = sc.get_pending_rewards(0, address.to_managed_address()).to_u64().unwrap();
+    });
+
+    pending
+}
+
+#[test]
+fn farm_rewards_are_split_by_stake() {
+    let mut world = setup_farm();
+    stake(&mut world, OWNER, 100_000);
+    world.current_block().block_timestamp(START_TIME + 100);
+    stake(&mut world, USER, 300_000);
+    world.current_block().block_timestamp(START_TIME + 200);
+
+    // OWNER alone for 100 seconds, then a quarter of the next 100
+    assert_eq!(pending_rewards(&mut world, OWNER), 12_500);
+    assert_eq!(pending_rewards(&mut world, USER), 7_500);
+
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.claim_rewards(0);
+    });
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 1_000_000 + 7_500)
+        .esdt_balance(LP_TOKEN, 1_000_000 - 300_000);
+    assert_eq!(pending_rewards(&mut world, USER), 0);
+}
+
+#[test]
+fn farm_stops_accruing_at_the_end() {
+    let mut world = setup_farm();
+    stake(&mut world, USER, 100_000);
+    world.current_block().block_timestamp(END_TIME + 500);
+    assert_eq!(pending_rewards(&mut world, USER), REWARDS);
+
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.unstake_lp(0, BigUint::from(100_000u64));
+    });
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000_000);
+    // rewards are kept after unstaking everything
+    assert_eq!(pending_rewards(&mut world, USER), REWARDS);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .returns(ExpectError(4, "not enough staked"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.unstake_lp(0, BigUint::from(1u64));
+        });
+}
+
+#[test]
+fn farm_compounds_rewards_into_staked_lp() {
+    let mut world = setup_farm();
+    stake(&mut world, USER, 100_000);
+    world.current_block().block_timestamp(START_TIME + 100);
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let lp_amount = sc.compound_rewards(0, BigUint::from(4_000u64));
+
+        let position = sc.farm_positions(0, &USER.to_managed_address()).get();
+        assert_eq!(position.amount, BigUint::from(100_000u64) + &lp_amount);
+        assert_eq!(position.pending, 0u64);
+        assert_eq!(sc.farms(0).get().total_staked, position.amount);
+        // 10_000 TOKEN zapped into a 2M / 2M pool, a bit less than half of it in LP after fees
+        assert!(lp_amount < 5_000u64);
+    });
+
+    // nothing left to compound in the same second
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .returns(ExpectError(4, "no rewards"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.compound_rewards(0, BigUint::zero());
+        });
+}
+
+#[test]
+fn farm_apr_and_leftover() {
+    let mut world = setup_farm();
+    world.current_block().block_timestamp(START_TIME + 500);
+    stake(&mut world, USER, 1_000_000);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        // 100 TOKEN per second for a year over 2_000_000 BASE worth of LP
+        assert_eq!(sc.get_farm_apr(0), 100 * 31_536_000 * 10_000 / 2_000_000);
+    });
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "farm in progress"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.withdraw_farm_leftover(0);
+        });
+    world.current_block().block_timestamp(END_TIME);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.withdraw_farm_leftover(0);
+        assert_eq!(sc.get_farm_apr(0), 0);
+    });
+    // nobody staked during the first half
+    world.check_account(OWNER).esdt_balance(TOKEN, INITIAL_BALANCE - 1_000_000 - REWARDS / 2);
+}
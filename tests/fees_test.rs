@@ -0,0 +1,149 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+// pair 1 is BASE (base) / OBASE (token), both being base tokens, so the fee is always charged on the input
+fn setup_base_pair() -> ScenarioWorld {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 1);
+
+    world
+}
+
+fn check_fees(world: &mut ScenarioWorld, token: TestTokenIdentifier, other_token: TestTokenIdentifier, amount: u64) {
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cummulated_fees().get(&token.to_token_identifier()), Some(BigUint::from(amount)));
+        assert_eq!(sc.cummulated_fees().get(&other_token.to_token_identifier()), None);
+
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(BigInt::from(fees), surplus);
+        }
+    });
+}
+
+#[test]
+fn fixed_input_fee_from_pair_token_is_accounted_in_pair_token() {
+    let mut world = setup_base_pair();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(OTHER_BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    check_fees(&mut world, OTHER_BASE_TOKEN, BASE_TOKEN, 10_000 * OWNER_FEE / 10_000);
+}
+
+#[test]
+fn fixed_input_fee_from_base_token_is_accounted_in_base_token() {
+    let mut world = setup_base_pair();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(OTHER_BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    check_fees(&mut world, BASE_TOKEN, OTHER_BASE_TOKEN, 10_000 * OWNER_FEE / 10_000);
+}
+
+#[test]
+fn fixed_output_fees_are_accounted_in_the_input_token() {
+    let mut world = setup_base_pair();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(OTHER_BASE_TOKEN, 0, 20_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output(BASE_TOKEN.to_token_identifier(), BigUint::from(10_000u64), OptionalValue::None);
+        });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.cummulated_fees().get(&OTHER_BASE_TOKEN.to_token_identifier()).is_some());
+        assert_eq!(sc.cummulated_fees().get(&BASE_TOKEN.to_token_identifier()), None);
+
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(BigInt::from(fees), surplus);
+        }
+    });
+}
+
+#[test]
+fn fee_from_non_base_token_is_accounted_in_base_token() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    // the fee is taken from the 9_900 base tokens the swap outputs before fees
+    check_fees(&mut world, BASE_TOKEN, TOKEN, 9_900 * OWNER_FEE / 10_000);
+}
+
+#[test]
+fn withdrawn_fees_match_the_accounting() {
+    let mut world = setup_base_pair();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(OTHER_BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.withdraw_fees();
+    });
+
+    world
+        .check_account(OWNER)
+        .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE - 1_000_000 + 100);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(fees, 0u64);
+            assert_eq!(surplus, BigInt::zero());
+        }
+    });
+}
+
+#[test]
+fn pair_fee_override_replaces_global_fees() {
+    let mut world = setup_base_pair();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_fees(1, 10, 40);
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(OTHER_BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(BASE_TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    check_fees(&mut world, OTHER_BASE_TOKEN, BASE_TOKEN, 10_000 * 40 / 10_000);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_fees(0), PairFees { lp_fee: LP_FEE, owner_fee: OWNER_FEE });
+        assert_eq!(sc.get_pair_fees(1), PairFees { lp_fee: 10, owner_fee: 40 });
+    });
+}
@@ -0,0 +1,141 @@
+mod dex_setup;
+mod flash_receiver_mock;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::flash_loan::FlashLoanModule;
+
+const RECEIVER: TestSCAddress = TestSCAddress::new("flash-receiver");
+const RECEIVER_CODE_PATH: MxscPath = MxscPath::new("output/flash-receiver-mock.mxsc.json");
+const FLASH_LOAN_FEE: u64 = 100;
+
+// BASE is lent by pair 0 (1_000_000 TOKEN / 1_000_000 BASE) and pair 1 (1_000_000 OBASE / 3_000_000 BASE),
+// pair 2 being empty
+fn setup_flash_loan() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.register_contract(RECEIVER_CODE_PATH, flash_receiver_mock::ContractBuilder);
+    world
+        .account(RECEIVER)
+        .nonce(1)
+        .code(RECEIVER_CODE_PATH)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE)
+        .esdt_balance(TOKEN, INITIAL_BALANCE);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 3_000_000));
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_flash_loan_fee(FLASH_LOAN_FEE);
+    });
+
+    world
+}
+
+// borrows `amount` BASE and has the receiver send back `repaid` of `repay_token`
+fn flash_loan_base(world: &mut ScenarioWorld, amount: u64, repay_token: TestTokenIdentifier, repaid: u64, expected_error: Option<&str>) {
+    let tx = world.tx().from(RECEIVER).to(DEX);
+    let whitebox = |sc: tfn_dex::ContractObj<DebugApi>| {
+        let mut args = MultiValueEncoded::new();
+        args.push(ManagedBuffer::from(repay_token.as_bytes()));
+        args.push(BigUint::<DebugApi>::from(repaid).to_bytes_be_buffer());
+        sc.flash_loan(BASE_TOKEN.to_token_identifier(), BigUint::from(amount), ManagedBuffer::from("repay"), args);
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(tfn_dex::contract_obj, whitebox),
+        None => tx.whitebox(tfn_dex::contract_obj, whitebox),
+    };
+}
+
+fn base_reserves(world: &mut ScenarioWorld) -> Vec<u64> {
+    let mut reserves = Vec::new();
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        reserves = (0..3).map(|id| sc.pairs(id).get().liquidity_base.to_u64().unwrap()).collect();
+    });
+
+    reserves
+}
+
+#[test]
+fn flash_loan_borrows_across_the_pairs_holding_the_token() {
+    let mut world = setup_flash_loan();
+    // more than any single pair holds, 1% fee
+    flash_loan_base(&mut world, 2_000_000, BASE_TOKEN, 2_020_000, None);
+
+    // the owner gets 100 / (200 + 100) of the fee, the pairs split the rest 1:3 with the dust going to the last one
+    assert_eq!(base_reserves(&mut world), vec![1_000_000 + 3_333, 3_000_000 + 10_001, 0]);
+    world.check_account(RECEIVER).esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 20_000);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cummulated_fees().get(&BASE_TOKEN.to_token_identifier()).unwrap(), 6_666u64);
+        assert!(!sc.reentrancy_lock().get());
+    });
+}
+
+#[test]
+fn flash_loan_overpaying_shares_the_whole_fee() {
+    let mut world = setup_flash_loan();
+    flash_loan_base(&mut world, 400_000, BASE_TOKEN, 412_000, None);
+
+    // 12_000 paid as fee: 4_000 for the owner, 2_000 and 6_000 for the pairs
+    assert_eq!(base_reserves(&mut world), vec![1_002_000, 3_006_000, 0]);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cummulated_fees().get(&BASE_TOKEN.to_token_identifier()).unwrap(), 4_000u64);
+    });
+}
+
+#[test]
+fn flash_loan_not_repaid_with_the_fee_fails() {
+    let mut world = setup_flash_loan();
+    flash_loan_base(&mut world, 2_000_000, BASE_TOKEN, 2_019_999, Some("flash loan not repaid"));
+    flash_loan_base(&mut world, 2_000_000, BASE_TOKEN, 0, Some("flash loan not repaid"));
+    flash_loan_base(&mut world, 2_000_000, TOKEN, 2_020_000, Some("wrong payment token(s)"));
+
+    assert_eq!(base_reserves(&mut world), vec![1_000_000, 3_000_000, 0]);
+    world.check_account(RECEIVER).esdt_balance(BASE_TOKEN, INITIAL_BALANCE);
+}
+
+#[test]
+fn flash_loan_only_lends_the_reserves_of_pairs_not_inactive() {
+    let mut world = setup_flash_loan();
+    flash_loan_base(&mut world, 4_000_001, BASE_TOKEN, 5_000_000, Some("no liquidity"));
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let mut pair = sc.pairs(1).get();
+        pair.state = PairState::Inactive;
+        sc.pairs(1).set(&pair);
+    });
+    flash_loan_base(&mut world, 1_000_001, BASE_TOKEN, 1_100_000, Some("no liquidity"));
+
+    // the whole fee goes to the only lending pair
+    flash_loan_base(&mut world, 1_000_000, BASE_TOKEN, 1_010_000, None);
+    assert_eq!(base_reserves(&mut world), vec![1_006_667, 3_000_000, 0]);
+}
+
+#[test]
+fn flash_loan_receiver_cannot_reenter() {
+    let mut world = setup_flash_loan();
+    set_pair_active(&mut world, 0);
+    // endpoint, payment and arguments of the nested call
+    let reentries: Vec<(&str, TestTokenIdentifier, u64, Vec<Vec<u8>>)> = vec![
+        ("flashLoan", BASE_TOKEN, 0, vec![BASE_TOKEN.as_bytes().to_vec(), vec![1], b"repay".to_vec()]),
+        ("flashSwap", BASE_TOKEN, 0, vec![vec![], TOKEN.as_bytes().to_vec(), vec![1], b"repay".to_vec()]),
+        ("swapFixedInput", BASE_TOKEN, 1_000, vec![TOKEN.as_bytes().to_vec(), vec![1]]),
+    ];
+
+    for (endpoint, token, amount, endpoint_args) in reentries {
+        world
+            .tx()
+            .from(RECEIVER)
+            .to(DEX)
+            .returns(ExpectError(4, "reentrant call"))
+            .whitebox(tfn_dex::contract_obj, |sc| {
+                let mut args = MultiValueEncoded::new();
+                args.push(ManagedBuffer::from(endpoint));
+                args.push(ManagedBuffer::from(token.as_bytes()));
+                args.push(BigUint::<DebugApi>::from(amount).to_bytes_be_buffer());
+                for arg in endpoint_args.iter() {
+                    args.push(ManagedBuffer::from(arg.as_slice()));
+                }
+                sc.flash_loan(BASE_TOKEN.to_token_identifier(), BigUint::from(10_000u64), ManagedBuffer::from("reenter"), args);
+            });
+    }
+}
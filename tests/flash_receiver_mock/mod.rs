@@ -0,0 +1,35 @@
+#![allow(dead_code)]
+
+multiversx_sc::imports!();
+
+// receiver of flash swaps and flash loans: pays back out of its own balances whatever it's told to
+#[multiversx_sc::contract]
+pub trait FlashReceiverMock {
+    #[init]
+    fn init(&self) {}
+
+    // sends back `amount` of `token`, whatever was received
+    #[payable("*")]
+    #[endpoint(repay)]
+    fn repay(&self, token: TokenIdentifier, amount: BigUint) {
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(&caller, &token, 0, &amount);
+    }
+
+    // calls `endpoint` back on the caller, paying it `amount` of `token` unless the amount is zero
+    #[payable("*")]
+    #[endpoint(reenter)]
+    fn reenter(&self, endpoint: ManagedBuffer, token: TokenIdentifier, amount: BigUint, args: MultiValueEncoded<ManagedBuffer>) {
+        let caller = self.blockchain().get_caller();
+        if amount == 0 {
+            self.tx().to(&caller).raw_call(endpoint).arguments_raw(args.to_arg_buffer()).sync_call();
+        } else {
+            self.tx()
+                .to(&caller)
+                .raw_call(endpoint)
+                .arguments_raw(args.to_arg_buffer())
+                .single_esdt(&token, 0, &amount)
+                .sync_call();
+        }
+    }
+}
@@ -0,0 +1,146 @@
+mod dex_setup;
+mod flash_receiver_mock;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::swap::SwapModule;
+
+const RECEIVER: TestSCAddress = TestSCAddress::new("flash-receiver");
+const RECEIVER_CODE_PATH: MxscPath = MxscPath::new("output/flash-receiver-mock.mxsc.json");
+
+// pair 0 holds 1_000_000 TOKEN and 1_000_000 BASE, the fee of flash swaps being 3%
+fn setup_flash_swap() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.register_contract(RECEIVER_CODE_PATH, flash_receiver_mock::ContractBuilder);
+    world
+        .account(RECEIVER)
+        .nonce(1)
+        .code(RECEIVER_CODE_PATH)
+        .esdt_balance(TOKEN, INITIAL_BALANCE)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE)
+        .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE)
+        .esdt_balance(LP_TOKEN, 1_000u64);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+fn repay_args(token: TestTokenIdentifier, amount: u64) -> MultiValueEncoded<DebugApi, ManagedBuffer<DebugApi>> {
+    let mut args = MultiValueEncoded::new();
+    args.push(ManagedBuffer::from(token.as_bytes()));
+    args.push(BigUint::<DebugApi>::from(amount).to_bytes_be_buffer());
+
+    args
+}
+
+// borrows `amount_out` TOKEN from pair 0 and has the receiver send back `repaid` of `repay_token`
+fn flash_swap_token(world: &mut ScenarioWorld, amount_out: u64, repay_token: TestTokenIdentifier, repaid: u64, expected_error: Option<&str>) {
+    let tx = world.tx().from(RECEIVER).to(DEX);
+    let whitebox = |sc: tfn_dex::ContractObj<DebugApi>| {
+        sc.flash_swap(0, TOKEN.to_token_identifier(), BigUint::from(amount_out), ManagedBuffer::from("repay"), repay_args(repay_token, repaid));
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(tfn_dex::contract_obj, whitebox),
+        None => tx.whitebox(tfn_dex::contract_obj, whitebox),
+    };
+}
+
+fn pair_reserves(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut reserves = (0, 0);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(0).get();
+        reserves = (pair.liquidity_token.to_u64().unwrap(), pair.liquidity_base.to_u64().unwrap());
+    });
+
+    reserves
+}
+
+#[test]
+fn flash_swap_repaid_with_the_fee_updates_the_reserves() {
+    let mut world = setup_flash_swap();
+    // (1_000_000 - 10_000 + repaid) * 10_000 - repaid * 300 >= 1_000_000 * 10_000
+    flash_swap_token(&mut world, 10_000, TOKEN, 10_310, None);
+
+    // the owner keeps 1% of what came in, the rest stays in the pair
+    assert_eq!(pair_reserves(&mut world), (1_000_000 - 10_000 + 10_310 - 103, 1_000_000));
+    world.check_account(RECEIVER).esdt_balance(TOKEN, INITIAL_BALANCE - 310);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.cummulated_fees().get(&TOKEN.to_token_identifier()).unwrap(), 103u64);
+        assert!(!sc.reentrancy_lock().get());
+    });
+
+    // the lock is released for the next calls
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::from(1u64), OptionalValue::None);
+        });
+}
+
+#[test]
+fn flash_swap_not_covering_the_fee_fails() {
+    let mut world = setup_flash_swap();
+    flash_swap_token(&mut world, 10_000, TOKEN, 0, Some("flash swap not repaid"));
+    flash_swap_token(&mut world, 10_000, TOKEN, 10_000, Some("flash swap not repaid"));
+    // one short of the fee adjusted invariant
+    flash_swap_token(&mut world, 10_000, TOKEN, 10_309, Some("flash swap not repaid"));
+
+    assert_eq!(pair_reserves(&mut world), (1_000_000, 1_000_000));
+    world.check_account(RECEIVER).esdt_balance(TOKEN, INITIAL_BALANCE);
+}
+
+#[test]
+fn flash_swap_can_be_repaid_in_the_other_token() {
+    let mut world = setup_flash_swap();
+    // 990_000 * ((1_000_000 + repaid) * 10_000 - repaid * 300) >= 1_000_000 * 1_000_000 * 10_000
+    flash_swap_token(&mut world, 10_000, BASE_TOKEN, 10_413, Some("flash swap not repaid"));
+    flash_swap_token(&mut world, 10_000, BASE_TOKEN, 10_414, None);
+
+    assert_eq!(pair_reserves(&mut world), (990_000, 1_000_000 + 10_414 - 104));
+    world.check_account(RECEIVER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE + 10_000)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 10_414);
+}
+
+#[test]
+fn flash_swap_repaid_with_another_token_fails() {
+    let mut world = setup_flash_swap();
+    flash_swap_token(&mut world, 10_000, OTHER_BASE_TOKEN, 20_000, Some("wrong payment token(s)"));
+}
+
+#[test]
+fn flash_swap_receiver_cannot_reenter() {
+    let mut world = setup_flash_swap();
+    // endpoint, payment and arguments of the nested call: swapFixedInput pays BASE for TOKEN with a minimum of 1,
+    // flashSwap borrows 1 TOKEN from pair 0 (encoded as empty bytes)
+    let reentries: Vec<(&str, TestTokenIdentifier, u64, Vec<Vec<u8>>)> = vec![
+        ("swapFixedInput", BASE_TOKEN, 1_000, vec![TOKEN.as_bytes().to_vec(), vec![1]]),
+        ("addLiquidity", TOKEN, 1_000, vec![]),
+        ("removeLiquidity", LP_TOKEN, 1_000, vec![]),
+        ("flashSwap", TOKEN, 0, vec![vec![], TOKEN.as_bytes().to_vec(), vec![1], b"repay".to_vec()]),
+    ];
+
+    for (endpoint, token, amount, endpoint_args) in reentries {
+        world
+            .tx()
+            .from(RECEIVER)
+            .to(DEX)
+            .returns(ExpectError(4, "reentrant call"))
+            .whitebox(tfn_dex::contract_obj, |sc| {
+                let mut args = MultiValueEncoded::new();
+                args.push(ManagedBuffer::from(endpoint));
+                args.push(ManagedBuffer::from(token.as_bytes()));
+                args.push(BigUint::<DebugApi>::from(amount).to_bytes_be_buffer());
+                for arg in endpoint_args.iter() {
+                    args.push(ManagedBuffer::from(arg.as_slice()));
+                }
+                sc.flash_swap(0, TOKEN.to_token_identifier(), BigUint::from(10_000u64), ManagedBuffer::from("reenter"), args);
+            });
+    }
+}
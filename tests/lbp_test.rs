@@ -0,0 +1,89 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::lbp::LbpModule;
+use tfn_dex::swap::SwapModule;
+
+const START_TIME: u64 = 1_000;
+const END_TIME: u64 = 11_000;
+
+// pair 0 moving from 90/10 to 50/50 TOKEN/BASE, starting at 1 BASE per TOKEN
+fn setup_lbp_pair() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.current_block().block_timestamp(START_TIME);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.start_lbp(0, 9_000, 5_000, START_TIME, END_TIME);
+    });
+    add_liquidity(&mut world, OWNER, (TOKEN, 900_000), (BASE_TOKEN, 100_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+fn quote_tokens_for_base(world: &mut ScenarioWorld, amount_in: u64) -> u64 {
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&BASE_TOKEN.to_token_identifier(), &TOKEN.to_token_identifier(), BigUint::from(amount_in))
+            .to_u64()
+            .unwrap();
+    });
+
+    amount_out
+}
+
+#[test]
+fn lbp_weights_shift_linearly() {
+    let mut world = setup_lbp_pair();
+    world.current_block().block_timestamp((START_TIME + END_TIME) / 2);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_weights(0).into_tuple(), (7_000, 3_000));
+    });
+}
+
+#[test]
+fn lbp_price_decreases_over_time() {
+    let mut world = setup_lbp_pair();
+    let early_out = quote_tokens_for_base(&mut world, 1_000);
+    world.current_block().block_timestamp((START_TIME + END_TIME) / 2);
+    let late_out = quote_tokens_for_base(&mut world, 1_000);
+
+    assert!(late_out > early_out);
+}
+
+#[test]
+fn lbp_pair_becomes_constant_product_after_the_end() {
+    let mut world = setup_lbp_pair();
+    world.current_block().block_timestamp(END_TIME + 1);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.pair_curve(0).get(), CurveType::ConstantProduct);
+        assert_eq!(sc.get_stored_weights(0), (5_000, 5_000));
+        assert!(sc.lbp_schedule(0).is_empty());
+    });
+}
+
+#[test]
+fn lbp_cannot_start_on_a_funded_pair() {
+    let mut world = setup_lbp_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "pair not empty"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.lbp_schedule(0).clear();
+            sc.start_lbp(0, 9_000, 5_000, START_TIME, END_TIME);
+        });
+}
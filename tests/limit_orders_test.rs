@@ -0,0 +1,119 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::limit_orders::LimitOrdersModule;
+use tfn_dex::swap::SwapModule;
+
+const KEEPER_FEE: u64 = 50;
+
+// pair 0 TOKEN/BASE at 1 BASE per TOKEN, with USER selling 1_000 TOKEN for at least 1_000 BASE
+fn setup_order() -> ScenarioWorld {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_order_keeper_fee(KEEPER_FEE);
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            assert_eq!(sc.place_order(BASE_TOKEN.to_token_identifier(), BigUint::from(1_000u64)), 1);
+        });
+
+    world
+}
+
+fn execute_orders(world: &mut ScenarioWorld, pair_id: usize, expected: usize) {
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let order_ids = MultiValueEncoded::from(ManagedVec::from_single_item(1u64));
+        assert_eq!(sc.execute_orders(pair_id, order_ids), expected);
+    });
+}
+
+#[test]
+fn order_is_filled_once_the_price_allows_it() {
+    let mut world = setup_order();
+    execute_orders(&mut world, 0, 0);
+
+    // buying TOKEN pushes its price up to ~1.2 BASE
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 100_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.can_execute_order(1));
+        amount_out = sc
+            .get_amount_out_view(&TOKEN.to_token_identifier(), &BASE_TOKEN.to_token_identifier(), BigUint::from(1_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    execute_orders(&mut world, 0, 1);
+
+    let keeper_fee = amount_out * KEEPER_FEE / 10_000;
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 1_000)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE + amount_out - keeper_fee);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.orders(1).is_empty());
+        assert!(sc.get_orders(USER.to_managed_address()).is_empty());
+        assert!(sc.escrowed_tokens().is_empty());
+    });
+}
+
+#[test]
+fn orders_of_another_pair_are_skipped() {
+    let mut world = setup_order();
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 1);
+    execute_orders(&mut world, 1, 0);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(!sc.orders(1).is_empty());
+    });
+}
+
+#[test]
+fn open_orders_are_listed_and_reconciled() {
+    let mut world = setup_order();
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let orders = sc.get_orders(USER.to_managed_address());
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders.get(0).amount_in, 1_000u64);
+        assert!(!sc.can_execute_order(1));
+
+        for entry in sc.get_fees_reconciliation() {
+            let (_, fees, surplus) = entry.into_tuple();
+            assert_eq!(BigInt::from(fees), surplus);
+        }
+    });
+}
+
+#[test]
+fn only_the_owner_can_cancel_an_order() {
+    let mut world = setup_order();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "only the order owner can call this function"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.cancel_order(1);
+        });
+    world.tx().from(USER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.cancel_order(1);
+    });
+
+    world.check_account(USER).esdt_balance(TOKEN, INITIAL_BALANCE);
+}
@@ -0,0 +1,223 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::{config::*, consts::MINIMUM_LIQUIDITY};
+use tfn_dex::liquidity::LiquidityModule;
+
+#[test]
+fn first_deposit_locks_minimum_liquidity() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 4_000_000));
+
+    // sqrt(1_000_000 * 4_000_000) = 2_000_000
+    world
+        .check_account(OWNER)
+        .esdt_balance(LP_TOKEN, 2_000_000 - MINIMUM_LIQUIDITY);
+    world.check_account(DEX).esdt_balance(LP_TOKEN, MINIMUM_LIQUIDITY);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(0).get();
+        assert_eq!(pair.lp_supply, 2_000_000u64);
+        assert_eq!(pair.liquidity_token, 1_000_000u64);
+        assert_eq!(pair.liquidity_base, 4_000_000u64);
+    });
+}
+
+#[test]
+fn first_deposit_below_minimum_liquidity_fails() {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 100))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "insufficient initial liquidity"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+}
+
+#[test]
+fn locked_liquidity_is_never_redeemed() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 4_000_000));
+    set_pair_active(&mut world, 0);
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 2_000_000 - MINIMUM_LIQUIDITY))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+
+    world.check_account(OWNER).esdt_balance(LP_TOKEN, 0u64);
+    world.check_account(DEX).esdt_balance(LP_TOKEN, MINIMUM_LIQUIDITY);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(0).get();
+        assert_eq!(pair.lp_supply, MINIMUM_LIQUIDITY);
+        assert_eq!(pair.liquidity_token, 500u64);
+        assert_eq!(pair.liquidity_base, 2_000u64);
+        assert_eq!(pair.state, PairState::Active);
+    });
+}
+
+#[test]
+fn later_deposits_are_proportional() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 4_000_000));
+    add_liquidity(&mut world, USER, (TOKEN, 100_000), (BASE_TOKEN, 500_000));
+
+    // only 400_000 base match 100_000 tokens, the rest is refunded
+    world
+        .check_account(USER)
+        .esdt_balance(LP_TOKEN, 200_000u64)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 400_000);
+}
+
+
+fn add_liquidity_with_bounds(
+    world: &mut ScenarioWorld,
+    amounts: (u64, u64),
+    bounds: (u64, u64, u64),
+    deadline: OptionalValue<u64>,
+    expected_error: &str,
+) {
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, amounts.0))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, amounts.1))
+        .returns(ExpectError(4, expected_error))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(
+                OptionalValue::Some(BigUint::from(bounds.0)),
+                OptionalValue::Some(BigUint::from(bounds.1)),
+                OptionalValue::Some(BigUint::from(bounds.2)),
+                deadline,
+            );
+        });
+}
+
+#[test]
+fn add_liquidity_respects_minimums_and_deadline() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 4_000_000));
+    world.current_block().block_timestamp(1_000);
+
+    // 100_000 tokens only match 400_000 of the 500_000 base sent, for 200_000 LP
+    add_liquidity_with_bounds(&mut world, (100_000, 500_000), (0, 0, 0), OptionalValue::Some(999), "deadline passed");
+    add_liquidity_with_bounds(&mut world, (200_000, 400_000), (100_001, 0, 0), OptionalValue::None, "insufficient token amount");
+    add_liquidity_with_bounds(&mut world, (100_000, 500_000), (0, 400_001, 0), OptionalValue::None, "insufficient base amount");
+    add_liquidity_with_bounds(&mut world, (100_000, 500_000), (0, 0, 200_001), OptionalValue::None, "insufficient lp amount");
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 100_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 500_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(
+                OptionalValue::Some(BigUint::from(100_000u64)),
+                OptionalValue::Some(BigUint::from(400_000u64)),
+                OptionalValue::Some(BigUint::from(200_000u64)),
+                OptionalValue::Some(1_000),
+            );
+        });
+    world.check_account(USER).esdt_balance(LP_TOKEN, 200_000u64);
+}
+
+#[test]
+fn remove_liquidity_respects_minimums_and_deadline() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 4_000_000));
+    world.current_block().block_timestamp(1_000);
+
+    // 20_000 LP out of 2_000_000 are worth 10_000 tokens and 40_000 base
+    for (bounds, deadline, expected_error) in [
+        ((0u64, 0u64), OptionalValue::Some(999), "deadline passed"),
+        ((10_001, 0), OptionalValue::None, "insufficient token amount"),
+        ((0, 40_001), OptionalValue::None, "insufficient base amount"),
+    ] {
+        world
+            .tx()
+            .from(OWNER)
+            .to(DEX)
+            .esdt(TestEsdtTransfer(LP_TOKEN, 0, 20_000))
+            .returns(ExpectError(4, expected_error))
+            .whitebox(tfn_dex::contract_obj, |sc| {
+                sc.remove_liquidity(
+                    OptionalValue::Some(BigUint::from(bounds.0)),
+                    OptionalValue::Some(BigUint::from(bounds.1)),
+                    deadline,
+                );
+            });
+    }
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 20_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_liquidity(
+                OptionalValue::Some(BigUint::from(10_000u64)),
+                OptionalValue::Some(BigUint::from(40_000u64)),
+                OptionalValue::Some(1_000),
+            );
+        });
+    world
+        .check_account(OWNER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 1_000_000 + 10_000)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 4_000_000 + 40_000);
+}
+
+// the optional arguments are positional: a deadline alone is read as the minimum token amount, so callers
+// wanting only a deadline have to pass every bound before it
+#[test]
+fn liquidity_optional_arguments_are_positional() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    world.current_block().block_timestamp(1_000);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("addLiquidity")
+        .argument(&0u64)
+        .argument(&0u64)
+        .argument(&0u64)
+        .argument(&999u64)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .returns(ExpectError(4, "deadline passed"))
+        .run();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("addLiquidity")
+        .argument(&999u64)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .run();
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000u64);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("removeLiquidity")
+        .argument(&0u64)
+        .argument(&0u64)
+        .argument(&999u64)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 1_000))
+        .returns(ExpectError(4, "deadline passed"))
+        .run();
+}
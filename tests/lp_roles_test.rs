@@ -0,0 +1,147 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::{config::*, consts::TOKEN_ISSUE_COST};
+use tfn_dex::liquidity::LiquidityModule;
+use tfn_dex::TFNDEXContract;
+
+const NEW_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("NEW-123456");
+const NEW_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("NEWBASE-123456");
+const ISSUER: TestAddress = TestAddress::new("issuer");
+
+fn set_roles_pending(world: &mut ScenarioWorld, pair_id: usize) {
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.lp_roles_state(pair_id).set(LpRolesState::Pending);
+    });
+}
+
+#[test]
+fn issued_lp_token_creates_a_pair_with_roles_granted() {
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.lp_token_issue_callback(
+            USER.to_managed_address(),
+            &BASE_TOKEN.to_token_identifier(),
+            &NEW_TOKEN.to_token_identifier(),
+            ManagedAsyncCallResult::Ok(NEW_LP_TOKEN.to_token_identifier()),
+        );
+    });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.last_pair_id().get(), 4);
+        let pair = sc.get_pair_by_tickers(&NEW_TOKEN.to_token_identifier(), &BASE_TOKEN.to_token_identifier()).unwrap();
+        assert_eq!(pair.id, 3);
+        assert_eq!(pair.lp_token, NEW_LP_TOKEN.to_token_identifier());
+        assert_eq!(sc.lp_roles_state(3).get(), LpRolesState::Granted);
+    });
+}
+
+#[test]
+fn failed_lp_token_issue_refunds_the_issue_cost() {
+    let mut world = setup_dex();
+    world.account(ISSUER).nonce(1).balance(TOKEN_ISSUE_COST);
+    world
+        .tx()
+        .from(ISSUER)
+        .to(DEX)
+        .egld(TOKEN_ISSUE_COST)
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.lp_token_issue_callback(
+                ISSUER.to_managed_address(),
+                &BASE_TOKEN.to_token_identifier(),
+                &NEW_TOKEN.to_token_identifier(),
+                ManagedAsyncCallResult::Err(ManagedAsyncCallError {
+                    err_code: 10,
+                    err_msg: ManagedBuffer::from("issue failed"),
+                }),
+            );
+        });
+
+    world.check_account(ISSUER).balance(TOKEN_ISSUE_COST);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.last_pair_id().get(), 3);
+        assert!(sc.get_pair_by_tickers(&NEW_TOKEN.to_token_identifier(), &BASE_TOKEN.to_token_identifier()).is_none());
+    });
+}
+
+#[test]
+fn roles_callback_only_grants_on_success() {
+    let mut world = setup_dex();
+    set_roles_pending(&mut world, 0);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.lp_token_roles_callback(
+            0,
+            ManagedAsyncCallResult::Err(ManagedAsyncCallError {
+                err_code: 10,
+                err_msg: ManagedBuffer::from("roles failed"),
+            }),
+        );
+        assert_eq!(sc.lp_roles_state(0).get(), LpRolesState::Pending);
+
+        sc.lp_token_roles_callback(0, ManagedAsyncCallResult::Ok(()));
+        assert_eq!(sc.lp_roles_state(0).get(), LpRolesState::Granted);
+    });
+}
+
+#[test]
+fn roles_retry_is_only_for_pending_pairs() {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "lp token roles already granted"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.retry_lp_token_roles(0);
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "pair not found"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.retry_lp_token_roles(5);
+        });
+}
+
+#[test]
+fn roles_retry_of_a_pending_pair() {
+    let mut world = setup_dex();
+    set_roles_pending(&mut world, 0);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.retry_lp_token_roles(0);
+    });
+
+    // the state only moves once lp_token_roles_callback gets the result of the system sc call
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.lp_roles_state(0).get(), LpRolesState::Pending);
+    });
+}
+
+#[test]
+fn liquidity_is_refused_while_roles_are_pending() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_roles_pending(&mut world, 0);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .returns(ExpectError(4, "lp token roles not granted"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 1_000))
+        .returns(ExpectError(4, "lp token roles not granted"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+}
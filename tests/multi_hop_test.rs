@@ -0,0 +1,242 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::swap::SwapModule;
+
+const WEGLD_OBASE_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("WEGLDOBASE-123456");
+const TOKEN_OBASE_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKENOBASE-123456");
+
+// TOKEN/BASE, OBASE/BASE and WEGLD/OBASE pairs, all holding 1_000_000 of each side
+fn setup_path() -> ScenarioWorld {
+    let mut world = setup_dex();
+    let wegld_pair = add_test_pair(&mut world, OTHER_BASE_TOKEN, WEGLD_TOKEN, WEGLD_OBASE_LP_TOKEN);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    add_liquidity(&mut world, OWNER, (WEGLD_TOKEN, 1_000_000), (OTHER_BASE_TOKEN, 1_000_000));
+    for pair_id in [0, 1, wegld_pair] {
+        set_pair_active(&mut world, pair_id);
+    }
+
+    world
+}
+
+fn to_path(tokens: &[TestTokenIdentifier]) -> ManagedVec<DebugApi, TokenIdentifier<DebugApi>> {
+    let mut path = ManagedVec::new();
+    for token in tokens {
+        path.push(token.to_token_identifier());
+    }
+
+    path
+}
+
+fn quote_path_out(world: &mut ScenarioWorld, amount_in: u64, tokens: &[TestTokenIdentifier]) -> u64 {
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_path_view(BigUint::from(amount_in), to_path(tokens).into_iter().collect())
+            .to_u64()
+            .unwrap();
+    });
+
+    amount_out
+}
+
+fn swap_path(world: &mut ScenarioWorld, amount_in: u64, min_amount_out: u64, tokens: &[TestTokenIdentifier], expected_error: Option<&str>) {
+    let tx = world.tx().from(USER).to(DEX).esdt(TestEsdtTransfer(tokens[0], 0, amount_in));
+    let whitebox = |sc: tfn_dex::ContractObj<DebugApi>| {
+        sc.swap_multi_hop(BigUint::from(min_amount_out), to_path(tokens), OptionalValue::None);
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(tfn_dex::contract_obj, whitebox),
+        None => tx.whitebox(tfn_dex::contract_obj, whitebox),
+    };
+}
+
+#[test]
+fn two_hop_swap_pays_the_quoted_amount() {
+    let mut world = setup_path();
+    let path = [TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN];
+    let amount_out = quote_path_out(&mut world, 10_000, &path);
+    assert!(amount_out > 9_000 && amount_out < 10_000, "{}", amount_out);
+
+    swap_path(&mut world, 10_000, amount_out, &path, None);
+    world.check_account(USER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE)
+        .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE + amount_out);
+}
+
+#[test]
+fn three_hop_swap_pays_the_quoted_amount() {
+    let mut world = setup_path();
+    let path = [TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN, WEGLD_TOKEN];
+    let two_hop_out = quote_path_out(&mut world, 10_000, &path[..3]);
+    let amount_out = quote_path_out(&mut world, 10_000, &path);
+    assert!(amount_out < two_hop_out, "{} >= {}", amount_out, two_hop_out);
+
+    swap_path(&mut world, 10_000, amount_out, &path, None);
+    world.check_account(USER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE + amount_out);
+}
+
+#[test]
+fn invalid_paths_are_refused() {
+    let mut world = setup_path();
+    swap_path(&mut world, 10_000, 0, &[TOKEN], Some("invalid swap path"));
+    swap_path(&mut world, 10_000, 0, &[TOKEN, BASE_TOKEN, TOKEN], Some("invalid swap path"));
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "invalid swap path"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_multi_hop(BigUint::zero(), to_path(&[BASE_TOKEN, TOKEN]), OptionalValue::None);
+        });
+    swap_path(&mut world, 10_000, 0, &[TOKEN, WEGLD_TOKEN], Some("pair not found"));
+}
+
+#[test]
+fn multi_hop_swap_below_min_amount_out_fails() {
+    let mut world = setup_path();
+    let path = [TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN];
+    let amount_out = quote_path_out(&mut world, 10_000, &path);
+
+    swap_path(&mut world, 10_000, amount_out + 1, &path, Some("insufficient output amount"));
+}
+
+#[test]
+fn multi_hop_fixed_output_refunds_the_unused_input() {
+    let mut world = setup_path();
+    let path = [TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN, WEGLD_TOKEN];
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_in = sc
+            .get_amount_in_path_view(BigUint::from(10_000u64), to_path(&path).into_iter().collect())
+            .to_u64()
+            .unwrap();
+    });
+    assert!(amount_in > 10_000 && amount_in < 12_000, "{}", amount_in);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 20_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_multi_hop_fixed_output(BigUint::from(10_000u64), to_path(&path), OptionalValue::None);
+        });
+    world.check_account(USER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - amount_in)
+        .esdt_balance(WEGLD_TOKEN, INITIAL_BALANCE + 10_000);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 5_000))
+        .returns(ExpectError(4, "insufficient input amount"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_multi_hop_fixed_output(BigUint::from(10_000u64), to_path(&path), OptionalValue::None);
+        });
+}
+
+#[test]
+fn multi_hop_swaps_past_their_deadline_fail() {
+    let mut world = setup_path();
+    world.current_block().block_timestamp(1_000);
+    let path = [TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN];
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "deadline passed"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_multi_hop(BigUint::zero(), to_path(&path), OptionalValue::Some(999));
+        });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "deadline passed"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_multi_hop_fixed_output(BigUint::from(1_000u64), to_path(&path), OptionalValue::Some(999));
+        });
+}
+
+fn best_route(world: &mut ScenarioWorld, token_in: TestTokenIdentifier, token_out: TestTokenIdentifier, amount_in: u64, max_hops: usize) -> (Vec<String>, u64) {
+    let mut route = (Vec::new(), 0);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let (path, amount_out) = sc
+            .get_best_route(token_in.to_token_identifier(), token_out.to_token_identifier(), BigUint::from(amount_in), max_hops)
+            .into_tuple();
+        route = (
+            path.iter().map(|token| token.to_string()).collect(),
+            amount_out.to_u64().unwrap(),
+        );
+    });
+
+    route
+}
+
+#[test]
+fn best_route_beats_a_shallow_direct_pair() {
+    let mut world = setup_path();
+    let direct_pair = add_test_pair(&mut world, OTHER_BASE_TOKEN, TOKEN, TOKEN_OBASE_LP_TOKEN);
+    add_liquidity(&mut world, OWNER, (TOKEN, 20_000), (OTHER_BASE_TOKEN, 20_000));
+    set_pair_active(&mut world, direct_pair);
+
+    let two_hop_out = quote_path_out(&mut world, 10_000, &[TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN]);
+    let direct_out = quote_path_out(&mut world, 10_000, &[TOKEN, OTHER_BASE_TOKEN]);
+    assert!(direct_out < two_hop_out, "{} >= {}", direct_out, two_hop_out);
+
+    let (path, amount_out) = best_route(&mut world, TOKEN, OTHER_BASE_TOKEN, 10_000, 3);
+    assert_eq!(path, vec![TOKEN.as_str(), BASE_TOKEN.as_str(), OTHER_BASE_TOKEN.as_str()]);
+    assert_eq!(amount_out, two_hop_out);
+
+    // limited to one hop only the direct pair is left
+    assert_eq!(best_route(&mut world, TOKEN, OTHER_BASE_TOKEN, 10_000, 1), (
+        vec![TOKEN.as_str().to_string(), OTHER_BASE_TOKEN.as_str().to_string()],
+        direct_out,
+    ));
+}
+
+#[test]
+fn best_route_follows_the_pairs_of_each_token() {
+    let mut world = setup_path();
+    let path = [TOKEN, BASE_TOKEN, OTHER_BASE_TOKEN, WEGLD_TOKEN];
+    let amount_out = quote_path_out(&mut world, 10_000, &path);
+
+    // the WEGLD/BASE pair of setup_dex has no liquidity, so the only way to WEGLD goes through OBASE
+    assert_eq!(best_route(&mut world, TOKEN, WEGLD_TOKEN, 10_000, 3), (
+        path.iter().map(|token| token.as_str().to_string()).collect(),
+        amount_out,
+    ));
+}
+
+#[test]
+fn best_route_fails_when_no_route_fits_the_hops() {
+    let mut world = setup_path();
+    for max_hops in [1, 2] {
+        world
+            .query()
+            .to(DEX)
+            .returns(ExpectError(4, "route not found"))
+            .whitebox(tfn_dex::contract_obj, |sc| {
+                sc.get_best_route(TOKEN.to_token_identifier(), WEGLD_TOKEN.to_token_identifier(), BigUint::from(10_000u64), max_hops);
+            });
+    }
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "wrong max hops"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_best_route(TOKEN.to_token_identifier(), WEGLD_TOKEN.to_token_identifier(), BigUint::from(10_000u64), 5);
+        });
+}
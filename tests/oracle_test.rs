@@ -0,0 +1,228 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::{config::*, consts::*};
+use tfn_dex::oracle::OracleModule;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+const START: u64 = 1_000;
+
+// TOKEN/BASE pair holding 1_000_000 of each side since START, so its first observation is at START
+fn setup_oracle() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.current_block().block_timestamp(START);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+fn swap_base_for_token(world: &mut ScenarioWorld, timestamp: u64, amount: u64) {
+    world.current_block().block_timestamp(timestamp);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::from(1u64), OptionalValue::None);
+        });
+}
+
+// spot prices of the pair as the oracle accumulates them: token in base and base in token
+fn spot_prices(world: &mut ScenarioWorld) -> (u128, u128) {
+    let mut prices = (0, 0);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(0).get();
+        let token = pair.liquidity_token.to_u64().unwrap() as u128;
+        let base = pair.liquidity_base.to_u64().unwrap() as u128;
+        prices = (base * PRICE_PRECISION as u128 / token, token * PRICE_PRECISION as u128 / base);
+    });
+
+    prices
+}
+
+fn twap(world: &mut ScenarioWorld, pair_id: usize, window: u64) -> (u128, u128) {
+    let mut prices = (0, 0);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let (token_price, base_price) = sc.get_twap(pair_id, window).into_tuple();
+        prices = (token_price.to_u64().unwrap() as u128, base_price.to_u64().unwrap() as u128);
+    });
+
+    prices
+}
+
+#[test]
+fn twap_is_the_time_weighted_average_of_the_spot_prices() {
+    let mut world = setup_oracle();
+    let precision = PRICE_PRECISION as u128;
+    swap_base_for_token(&mut world, START + 60, 50_000);
+    let (token_price, base_price) = spot_prices(&mut world);
+    assert!(token_price > precision && base_price < precision);
+
+    // 60 seconds at the initial 1:1 price, then 120 seconds at the price after the swap
+    world.current_block().block_timestamp(START + 180);
+    assert_eq!(twap(&mut world, 0, 180), (
+        (60 * precision + 120 * token_price) / 180,
+        (60 * precision + 120 * base_price) / 180,
+    ));
+
+    // windows are rounded back to the latest observation at or before their start
+    assert_eq!(twap(&mut world, 0, 120), (token_price, base_price));
+    assert_eq!(twap(&mut world, 0, 100), (token_price, base_price));
+}
+
+// marginal price of the token in base on a stable swap curve, D being solved like the contract does
+fn stable_spot_price(amp: f64, x: f64, y: f64) -> f64 {
+    let ann = amp * 4.0;
+    let mut d = x + y;
+    for _ in 0..256 {
+        let d_p = d * d * d / (4.0 * x * y);
+        d = (ann * (x + y) + 2.0 * d_p) * d / ((ann - 1.0) * d + 3.0 * d_p);
+    }
+    let amp_term = 16.0 * amp * x * x * y * y;
+
+    (amp_term + d * d * d * y) / (amp_term + d * d * d * x)
+}
+
+#[test]
+fn stable_pair_twap_follows_the_curve() {
+    let mut world = setup_dex();
+    world.current_block().block_timestamp(START);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_stable_swap(1, 100, 18, 18);
+    });
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 1);
+    world.current_block().block_timestamp(START + 60);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 300_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(1u64), OptionalValue::None);
+        });
+
+    let mut reserves = (0.0, 0.0);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(1).get();
+        reserves = (pair.liquidity_token.to_u64().unwrap() as f64, pair.liquidity_base.to_u64().unwrap() as f64);
+    });
+    let expected = stable_spot_price(100.0, reserves.0, reserves.1);
+    world.current_block().block_timestamp(START + 180);
+    let (token_price, base_price) = twap(&mut world, 1, 120);
+    let precision = PRICE_PRECISION as f64;
+    assert!((token_price as f64 / precision / expected - 1.0).abs() < 1e-6, "{} {}", token_price, expected);
+    assert!((base_price as f64 / precision * expected - 1.0).abs() < 1e-6, "{} {}", base_price, expected);
+
+    // close to the peg, far from the reserve ratio
+    let reserve_ratio = reserves.1 / reserves.0;
+    assert!(expected > 1.0 && expected < 1.01 && reserve_ratio > 1.5, "{} {}", expected, reserve_ratio);
+}
+
+#[test]
+fn weighted_pair_twap_follows_the_weights() {
+    let mut world = setup_dex();
+    world.current_block().block_timestamp(START);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_weights(0, 8_000, 2_000);
+    });
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 250_000));
+    world.current_block().block_timestamp(START + 60);
+
+    // 250_000 / 2_000 BASE for 1_000_000 / 8_000 TOKEN
+    let precision = PRICE_PRECISION as u128;
+    assert_eq!(twap(&mut world, 0, 60), (precision, precision));
+}
+
+#[test]
+fn observations_wrap_around_once_the_buffer_is_full() {
+    let mut world = setup_oracle();
+    let swaps = PRICE_OBSERVATIONS_CAPACITY as u64 + 6;
+    for i in 1..=swaps {
+        swap_base_for_token(&mut world, START + i * PRICE_OBSERVATION_PERIOD, 1_000);
+    }
+
+    // the observation of START and the first 6 swaps were overwritten, the VecMapper counting from 1
+    let oldest = START + 7 * PRICE_OBSERVATION_PERIOD;
+    let now = START + swaps * PRICE_OBSERVATION_PERIOD;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let observations = sc.price_observations(0);
+        assert_eq!(observations.len(), PRICE_OBSERVATIONS_CAPACITY);
+        assert_eq!(sc.last_price_observation_index(0).get(), 7);
+        assert_eq!(observations.get(7).timestamp, now);
+        assert_eq!(observations.get(8).timestamp, oldest);
+        assert_eq!(observations.iter().map(|observation| observation.timestamp).min(), Some(oldest));
+    });
+
+    twap(&mut world, 0, now - oldest);
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "not enough price observations"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_twap(0, now - oldest + 1);
+        });
+}
+
+#[test]
+fn observations_are_only_taken_once_per_period() {
+    let mut world = setup_oracle();
+    swap_base_for_token(&mut world, START + 10, 1_000);
+    swap_base_for_token(&mut world, START + PRICE_OBSERVATION_PERIOD - 1, 1_000);
+    swap_base_for_token(&mut world, START + PRICE_OBSERVATION_PERIOD, 1_000);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let timestamps: Vec<u64> = sc.price_observations(0).iter().map(|observation| observation.timestamp).collect();
+        assert_eq!(timestamps, vec![START, START + PRICE_OBSERVATION_PERIOD]);
+    });
+}
+
+#[test]
+fn twap_needs_a_valid_window_and_old_enough_observations() {
+    let mut world = setup_oracle();
+    world.current_block().block_timestamp(START + 100);
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "wrong twap window"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_twap(0, 0);
+        });
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "pair not found"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_twap(99, 100);
+        });
+
+    // the window starts before the first observation
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "not enough price observations"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_twap(0, 101);
+        });
+    // and before the chain started
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "not enough price observations"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_twap(0, START + 101);
+        });
+    // a pair that never had liquidity has no observation at all
+    world
+        .query()
+        .to(DEX)
+        .returns(ExpectError(4, "not enough price observations"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.get_twap(1, 1);
+        });
+}
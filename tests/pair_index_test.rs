@@ -0,0 +1,97 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::liquidity::LiquidityModule;
+use tfn_dex::TFNDEXContract;
+
+const NEW_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("NEW-123456");
+const NEW_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("NEWOBASE-123456");
+
+fn pair_id_by_tickers(world: &mut ScenarioWorld, first: TestTokenIdentifier, second: TestTokenIdentifier) -> Option<usize> {
+    let mut id = None;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        id = sc.get_pair_by_tickers(&first.to_token_identifier(), &second.to_token_identifier()).map(|pair| pair.id);
+    });
+
+    id
+}
+
+#[test]
+fn pairs_are_found_by_tokens_in_both_orders_and_by_lp_token() {
+    let mut world = setup_dex();
+    assert_eq!(pair_id_by_tickers(&mut world, BASE_TOKEN, TOKEN), Some(0));
+    assert_eq!(pair_id_by_tickers(&mut world, TOKEN, BASE_TOKEN), Some(0));
+    assert_eq!(pair_id_by_tickers(&mut world, OTHER_BASE_TOKEN, BASE_TOKEN), Some(1));
+    assert_eq!(pair_id_by_tickers(&mut world, TOKEN, OTHER_BASE_TOKEN), None);
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_by_lp_token(&LP_TOKEN.to_token_identifier()).unwrap().id, 0);
+        assert_eq!(sc.get_pair_by_lp_token(&WEGLD_LP_TOKEN.to_token_identifier()).unwrap().id, 2);
+        assert!(sc.get_pair_by_lp_token(&TOKEN.to_token_identifier()).is_none());
+
+        let base_pairs: Vec<usize> = sc.base_token_pairs(&BASE_TOKEN.to_token_identifier()).iter().collect();
+        assert_eq!(base_pairs, vec![0, 1, 2]);
+        assert!(sc.base_token_pairs(&OTHER_BASE_TOKEN.to_token_identifier()).is_empty());
+    });
+}
+
+#[test]
+fn created_pairs_are_indexed() {
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.lp_token_issue_callback(
+            OWNER.to_managed_address(),
+            &OTHER_BASE_TOKEN.to_token_identifier(),
+            &NEW_TOKEN.to_token_identifier(),
+            ManagedAsyncCallResult::Ok(NEW_LP_TOKEN.to_token_identifier()),
+        );
+    });
+
+    assert_eq!(pair_id_by_tickers(&mut world, NEW_TOKEN, OTHER_BASE_TOKEN), Some(3));
+    assert_eq!(pair_id_by_tickers(&mut world, OTHER_BASE_TOKEN, NEW_TOKEN), Some(3));
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_by_lp_token(&NEW_LP_TOKEN.to_token_identifier()).unwrap().id, 3);
+        assert!(sc.base_token_pairs(&OTHER_BASE_TOKEN.to_token_identifier()).contains(&3));
+    });
+}
+
+#[test]
+fn pairs_stay_indexed_once_emptied() {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 1_000_000 - 1_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+
+    assert_eq!(pair_id_by_tickers(&mut world, TOKEN, BASE_TOKEN), Some(0));
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_by_lp_token(&LP_TOKEN.to_token_identifier()).unwrap().id, 0);
+    });
+}
+
+#[test]
+fn base_tokens_can_only_be_removed_once_no_pair_uses_them_as_base() {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "base token in use"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_base_token(BASE_TOKEN.to_token_identifier());
+        });
+
+    // OBASE is only the token side of pair 1
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.remove_base_token(OTHER_BASE_TOKEN.to_token_identifier());
+        assert!(!sc.base_tokens().contains(&OTHER_BASE_TOKEN.to_token_identifier()));
+    });
+}
@@ -0,0 +1,203 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::{config::*, consts::*};
+use tfn_dex::oracle::OracleModule;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+// pair 1 is BASE (base) / OBASE (token), both being base tokens
+fn setup_stable_pair(amp: u64) -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_stable_swap(1, amp, 18, 18);
+    });
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 1);
+
+    world
+}
+
+fn quote_amount_out(world: &mut ScenarioWorld, amount_in: u64) -> u64 {
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&BASE_TOKEN.to_token_identifier(), &OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(amount_in))
+            .to_u64()
+            .unwrap();
+    });
+
+    amount_out
+}
+
+#[test]
+fn stable_swap_has_less_slippage_than_constant_product() {
+    let mut world = setup_stable_pair(100);
+    let amount_out = quote_amount_out(&mut world, 100_000);
+    let amount_in_after_fee = 100_000 - 100_000 * (LP_FEE + OWNER_FEE) / 10_000;
+    let constant_product_out = amount_in_after_fee * 1_000_000 / (1_000_000 + amount_in_after_fee);
+    assert!(amount_out > constant_product_out);
+    assert!(amount_out < amount_in_after_fee && amount_out > amount_in_after_fee * 99 / 100);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 100_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(amount_out), OptionalValue::None);
+        });
+    world.check_account(USER).esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE + amount_out);
+}
+
+#[test]
+fn stable_swap_fixed_output_charges_the_quoted_input() {
+    let mut world = setup_stable_pair(100);
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_in = sc
+            .get_amount_in_view(&BASE_TOKEN.to_token_identifier(), &OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(50_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 100_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output(OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(50_000u64), OptionalValue::None);
+        });
+
+    world.check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - amount_in)
+        .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE + 50_000);
+}
+
+#[test]
+fn amplification_ramps_linearly() {
+    let mut world = setup_stable_pair(100);
+    world.current_block().block_timestamp(1_000);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.ramp_pair_amp(1, 200, 1_000 + 2 * 86_400);
+    });
+
+    world.current_block().block_timestamp(1_000 + 86_400);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_amp(1), 150);
+    });
+    world.current_block().block_timestamp(1_000 + 3 * 86_400);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.get_pair_amp(1), 200);
+    });
+}
+
+#[test]
+fn stable_swap_needs_an_empty_pair_of_base_tokens() {
+    let mut world = setup_stable_pair(100);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "pair not empty"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.set_pair_stable_swap(1, 50, 18, 18);
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "wrong base token"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.set_pair_stable_swap(0, 50, 18, 18);
+        });
+}
+
+fn setup_scaled_stable_pair(token_decimals: u32, token_amount: u64) -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_stable_swap(1, 100, token_decimals, 9);
+    });
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, token_amount), (BASE_TOKEN, 1_000_000_000));
+    set_pair_active(&mut world, 1);
+
+    world
+}
+
+#[test]
+fn stable_pairs_scale_tokens_of_different_decimals() {
+    // the same pool in value, OBASE having 6 decimals instead of 9
+    let mut same_decimals = setup_scaled_stable_pair(9, 1_000_000_000);
+    let mut world = setup_scaled_stable_pair(6, 1_000_000);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let multipliers = sc.pair_precision_multipliers(1).get();
+        assert_eq!((multipliers.token_multiplier, multipliers.base_multiplier), (1_000, 1));
+        // 1 OBASE is worth 1 BASE, that is 1_000 of its units for one of the base
+        let (token_price, base_price) = sc.get_spot_prices(&sc.pairs(1).get());
+        assert_eq!(token_price, BigUint::from(PRICE_PRECISION) * 1_000u64);
+        assert_eq!(base_price, PRICE_PRECISION / 1_000);
+    });
+
+    let scaled_out = quote_amount_out(&mut world, 100_000_000);
+    let same_decimals_out = quote_amount_out(&mut same_decimals, 100_000_000);
+    assert!(same_decimals_out / 1_000 - scaled_out <= 1);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 100_000_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(scaled_out), OptionalValue::None);
+        });
+    world.check_account(USER).esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE + scaled_out);
+
+    // 0.1 BASE buys close to 0.097 OBASE after the 3% fee, counted in its own 6 decimals
+    assert!(scaled_out > 96_000 && scaled_out < 97_000);
+}
+
+#[test]
+fn stable_pairs_refuse_more_than_18_decimals() {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "wrong decimals"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.set_pair_stable_swap(1, 50, 19, 18);
+        });
+}
+
+#[test]
+fn stable_swap_fixed_output_of_dust_charges_at_least_one() {
+    // newton's error on this pool puts the new BASE reserve one under the old one when 1 OBASE goes out
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_stable_swap(1, 1, 18, 18);
+    });
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 56_456), (BASE_TOKEN, 30_464));
+    set_pair_active(&mut world, 1);
+
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_in = sc
+            .get_amount_in_view(&BASE_TOKEN.to_token_identifier(), &OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(1u64))
+            .to_u64()
+            .unwrap();
+    });
+    assert!(amount_in >= 1);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output(OTHER_BASE_TOKEN.to_token_identifier(), BigUint::from(1u64), OptionalValue::None);
+        });
+
+    world.check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - amount_in)
+        .esdt_balance(OTHER_BASE_TOKEN, INITIAL_BALANCE + 1);
+}
@@ -0,0 +1,124 @@
+mod call_target_mock;
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use call_target_mock::CallTargetMock;
+use tfn_dex::common::config::*;
+use tfn_dex::swap::SwapModule;
+
+const TARGET: TestSCAddress = TestSCAddress::new("call-target");
+const TARGET_CODE_PATH: MxscPath = MxscPath::new("output/call-target-mock.mxsc.json");
+
+// pair 0 holding 1_000_000 TOKEN and 1_000_000 BASE, the calls going through the endpoints so that the async call
+// to the target and its callback run
+fn setup_swap_and_call() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.register_contract(TARGET_CODE_PATH, call_target_mock::ContractBuilder);
+    world.account(TARGET).nonce(1).code(TARGET_CODE_PATH);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+fn quote_base_for_token(world: &mut ScenarioWorld, amount_in: u64) -> u64 {
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&BASE_TOKEN.to_token_identifier(), &TOKEN.to_token_identifier(), BigUint::from(amount_in))
+            .to_u64()
+            .unwrap();
+    });
+
+    amount_out
+}
+
+#[test]
+fn swap_output_is_delivered_to_the_target() {
+    let mut world = setup_swap_and_call();
+    let amount_out = quote_base_for_token(&mut world, 10_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("swapFixedInputAndCall")
+        .argument(&TOKEN)
+        .argument(&amount_out)
+        .argument(&TARGET)
+        .argument(&"deposit")
+        .argument(&USER)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .run();
+
+    world.check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(TOKEN, INITIAL_BALANCE);
+    world.check_account(TARGET).esdt_balance(TOKEN, amount_out);
+    world.query().to(TARGET).whitebox(call_target_mock::contract_obj, |sc| {
+        assert_eq!(sc.deposits(&USER.to_managed_address()).get(), amount_out);
+    });
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.pairs(0).get().liquidity_token, 1_000_000 - amount_out);
+    });
+}
+
+#[test]
+fn swap_output_is_refunded_when_the_target_call_fails() {
+    let mut world = setup_swap_and_call();
+    let amount_out = quote_base_for_token(&mut world, 10_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("swapFixedInputAndCall")
+        .argument(&TOKEN)
+        .argument(&0u64)
+        .argument(&TARGET)
+        .argument(&"reject")
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .run();
+
+    // the swap stays done, its output going back to the caller
+    world.check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(TOKEN, INITIAL_BALANCE + amount_out);
+    world.check_account(TARGET).esdt_balance(TOKEN, 0u64);
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.pairs(0).get().liquidity_token, 1_000_000 - amount_out);
+    });
+}
+
+#[test]
+fn swap_and_call_checks_the_destination_and_the_output() {
+    let mut world = setup_swap_and_call();
+    let amount_out = quote_base_for_token(&mut world, 10_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("swapFixedInputAndCall")
+        .argument(&TOKEN)
+        .argument(&0u64)
+        .argument(&USER)
+        .argument(&"deposit")
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "wrong address"))
+        .run();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .raw_call("swapFixedInputAndCall")
+        .argument(&TOKEN)
+        .argument(&(amount_out + 1))
+        .argument(&TARGET)
+        .argument(&"deposit")
+        .argument(&USER)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "insufficient output amount"))
+        .run();
+
+    world.check_account(USER).esdt_balance(BASE_TOKEN, INITIAL_BALANCE);
+}
@@ -0,0 +1,131 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::swap::SwapModule;
+
+const RECIPIENT: TestAddress = TestAddress::new("recipient");
+
+fn setup_token_pair() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.account(RECIPIENT).nonce(1);
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+#[test]
+fn swap_fixed_input_to_pays_the_recipient() {
+    let mut world = setup_token_pair();
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&BASE_TOKEN.to_token_identifier(), &TOKEN.to_token_identifier(), BigUint::from(10_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input_to(
+                RECIPIENT.to_managed_address(),
+                TOKEN.to_token_identifier(),
+                BigUint::zero(),
+                OptionalValue::None,
+            );
+        });
+
+    world.check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - 10_000)
+        .esdt_balance(TOKEN, INITIAL_BALANCE);
+    world.check_account(RECIPIENT).esdt_balance(TOKEN, amount_out);
+}
+
+#[test]
+fn swap_fixed_output_to_refunds_the_caller() {
+    let mut world = setup_token_pair();
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_in = sc
+            .get_amount_in_view(&BASE_TOKEN.to_token_identifier(), &TOKEN.to_token_identifier(), BigUint::from(5_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output_to(
+                RECIPIENT.to_managed_address(),
+                TOKEN.to_token_identifier(),
+                BigUint::from(5_000u64),
+                OptionalValue::None,
+            );
+        });
+
+    world.check_account(USER)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE - amount_in)
+        .esdt_balance(TOKEN, INITIAL_BALANCE);
+    world.check_account(RECIPIENT).esdt_balance(TOKEN, 5_000);
+}
+
+#[test]
+fn swaps_past_their_deadline_fail() {
+    let mut world = setup_token_pair();
+    world.current_block().block_timestamp(1_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "deadline passed"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::Some(999));
+        });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "deadline passed"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output(TOKEN.to_token_identifier(), BigUint::from(5_000u64), OptionalValue::Some(999));
+        });
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::Some(1_000));
+        });
+}
+
+#[test]
+fn swap_below_min_amount_out_fails() {
+    let mut world = setup_token_pair();
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&BASE_TOKEN.to_token_identifier(), &TOKEN.to_token_identifier(), BigUint::from(10_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .returns(ExpectError(4, "insufficient output amount"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::from(amount_out + 1), OptionalValue::None);
+        });
+}
@@ -0,0 +1,171 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc::storage::{mappers::SingleValueMapper, StorageKey};
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::lbp::LbpModule;
+use tfn_dex::liquidity::LiquidityModule;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+multiversx_sc::derive_imports!();
+
+// the Pair layout of deployments that predate the per pair curve and weights
+#[derive(TopEncode, TopDecode)]
+struct LegacyPair<M: ManagedTypeApi> {
+    id: usize,
+    state: PairState,
+    token: TokenIdentifier<M>,
+    base_token: TokenIdentifier<M>,
+    lp_token: TokenIdentifier<M>,
+    lp_supply: BigUint<M>,
+    liquidity_token: BigUint<M>,
+    liquidity_base: BigUint<M>,
+}
+
+fn seed_legacy_pair(sc: &tfn_dex::ContractObj<DebugApi>, pair: LegacyPair<DebugApi>) {
+    let mut key = StorageKey::new(b"pairs");
+    key.append_item(&pair.id);
+    SingleValueMapper::<DebugApi, LegacyPair<DebugApi>>::new(key).set(&pair);
+    sc.last_pair_id().set(pair.id + 1);
+}
+
+// a deployment written by an older version of the contract: a funded TOKEN/BASE pair whose LP token roles
+// are held by the contract, and an empty OBASE/BASE pair whose roles never came through
+fn setup_legacy_dex() -> ScenarioWorld {
+    let mut world = world();
+    for address in [OWNER, USER] {
+        world
+            .account(address)
+            .nonce(1)
+            .esdt_balance(BASE_TOKEN, INITIAL_BALANCE)
+            .esdt_balance(TOKEN, INITIAL_BALANCE);
+    }
+    world
+        .account(DEX)
+        .nonce(1)
+        .code(CODE_PATH)
+        .owner(OWNER)
+        .esdt_balance(BASE_TOKEN, 1_000_000)
+        .esdt_balance(TOKEN, 1_000_000)
+        .esdt_roles(LP_TOKEN, vec!["ESDTRoleLocalMint".to_string(), "ESDTRoleLocalBurn".to_string()]);
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.state().set(State::Active);
+        sc.lp_fee().set(LP_FEE);
+        sc.owner_fee().set(OWNER_FEE);
+        sc.base_tokens().insert(BASE_TOKEN.to_token_identifier());
+        seed_legacy_pair(&sc, LegacyPair {
+            id: 0,
+            state: PairState::Active,
+            token: TOKEN.to_token_identifier(),
+            base_token: BASE_TOKEN.to_token_identifier(),
+            lp_token: LP_TOKEN.to_token_identifier(),
+            lp_supply: BigUint::from(1_000_000u64),
+            liquidity_token: BigUint::from(1_000_000u64),
+            liquidity_base: BigUint::from(1_000_000u64),
+        });
+        seed_legacy_pair(&sc, LegacyPair {
+            id: 1,
+            state: PairState::ActiveNoSwap,
+            token: OTHER_BASE_TOKEN.to_token_identifier(),
+            base_token: BASE_TOKEN.to_token_identifier(),
+            lp_token: BASE_LP_TOKEN.to_token_identifier(),
+            lp_supply: BigUint::zero(),
+            liquidity_token: BigUint::zero(),
+            liquidity_base: BigUint::zero(),
+        });
+    });
+
+    world
+}
+
+#[test]
+fn legacy_pairs_keep_working_after_upgrade() {
+    let mut world = setup_legacy_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.upgrade();
+    });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.pairs(0).get().liquidity_token, 1_000_000u64);
+        assert_eq!(sc.pair_curve(0).get(), CurveType::ConstantProduct);
+        assert_eq!(sc.get_pair_weights(0).into_tuple(), (5_000, 5_000));
+    });
+
+    // constant product quote of 10_000 BASE, the fee being taken from the base token input
+    let amount_in = 10_000 - 10_000 * (LP_FEE + OWNER_FEE) / 10_000;
+    let amount_out = amount_in * 1_000_000 / (1_000_000 + amount_in);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::from(amount_out), OptionalValue::None);
+        });
+    world.check_account(USER).esdt_balance(TOKEN, INITIAL_BALANCE + amount_out);
+}
+
+#[test]
+fn upgrade_grants_lp_roles_to_pairs_holding_them() {
+    let mut world = setup_legacy_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.upgrade();
+    });
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert_eq!(sc.lp_roles_state(0).get(), LpRolesState::Granted);
+        assert_eq!(sc.lp_roles_state(1).get(), LpRolesState::Pending);
+    });
+
+    // liquidity providers of the legacy pair can withdraw again
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.add_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None, OptionalValue::None, OptionalValue::None);
+        });
+    world.check_account(OWNER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE)
+        .esdt_balance(LP_TOKEN, 0u64);
+}
+
+#[test]
+fn upgrade_backfills_the_pair_indexes() {
+    let mut world = setup_legacy_dex();
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(sc.get_pair_by_tickers(&TOKEN.to_token_identifier(), &BASE_TOKEN.to_token_identifier()).is_none());
+    });
+
+    // a second upgrade finds the indexes already there
+    for _ in 0..2 {
+        world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+            sc.upgrade();
+        });
+    }
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let token = TOKEN.to_token_identifier();
+        let base_token = BASE_TOKEN.to_token_identifier();
+        assert_eq!(sc.get_pair_by_tickers(&token, &base_token).unwrap().id, 0);
+        assert_eq!(sc.get_pair_by_tickers(&base_token, &token).unwrap().id, 0);
+        assert_eq!(sc.get_pair_by_tickers(&OTHER_BASE_TOKEN.to_token_identifier(), &base_token).unwrap().id, 1);
+        assert_eq!(sc.get_pair_by_lp_token(&BASE_LP_TOKEN.to_token_identifier()).unwrap().id, 1);
+        assert_eq!(sc.base_token_pairs(&base_token).len(), 2);
+    });
+}
@@ -0,0 +1,151 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::limit_orders::LimitOrdersModule;
+use tfn_dex::swap::SwapModule;
+use tfn_dex::TFNDEXContract;
+
+const TOKEN_OBASE_LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKENOBASE-123456");
+
+// pair 0 as an 80/20 TOKEN/BASE pool priced at 1 BASE per TOKEN
+fn setup_weighted_pair() -> ScenarioWorld {
+    let mut world = setup_dex();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        sc.set_pair_weights(0, 8_000, 2_000);
+    });
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 250_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+fn weighted_amount_out(amount_in: f64, liquidity_in: f64, liquidity_out: f64, weight_in: f64, weight_out: f64) -> f64 {
+    liquidity_out * (1.0 - (liquidity_in / (liquidity_in + amount_in)).powf(weight_in / weight_out))
+}
+
+#[test]
+fn weighted_swap_follows_the_balancer_formula() {
+    let mut world = setup_weighted_pair();
+    let mut amount_out = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_out = sc
+            .get_amount_out_view(&BASE_TOKEN.to_token_identifier(), &TOKEN.to_token_identifier(), BigUint::from(10_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    let amount_in_after_fee = 10_000.0 * (1.0 - (LP_FEE + OWNER_FEE) as f64 / 10_000.0);
+    let expected = weighted_amount_out(amount_in_after_fee, 250_000.0, 1_000_000.0, 2_000.0, 8_000.0);
+    assert!((amount_out as f64 - expected).abs() <= 2.0, "{} != {}", amount_out, expected);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::from(amount_out), OptionalValue::None);
+        });
+    world.check_account(USER).esdt_balance(TOKEN, INITIAL_BALANCE + amount_out);
+}
+
+#[test]
+fn weighted_swap_fixed_output_charges_the_quoted_input() {
+    let mut world = setup_weighted_pair();
+    let mut amount_in = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        amount_in = sc
+            .get_amount_in_view(&TOKEN.to_token_identifier(), &BASE_TOKEN.to_token_identifier(), BigUint::from(5_000u64))
+            .to_u64()
+            .unwrap();
+    });
+    // 5_000 BASE plus the fee on the output, at about 1 TOKEN per BASE and with some price impact
+    assert!(amount_in > 5_150 && amount_in < 5_300, "{}", amount_in);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 10_000))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_output(BASE_TOKEN.to_token_identifier(), BigUint::from(5_000u64), OptionalValue::None);
+        });
+    world.check_account(USER)
+        .esdt_balance(TOKEN, INITIAL_BALANCE - amount_in)
+        .esdt_balance(BASE_TOKEN, INITIAL_BALANCE + 5_000);
+}
+
+#[test]
+fn weighted_swap_above_max_ratio_fails() {
+    let mut world = setup_weighted_pair();
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 200_000))
+        .returns(ExpectError(4, "amount exceeds max ratio"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(TOKEN.to_token_identifier(), BigUint::zero(), OptionalValue::None);
+        });
+}
+
+#[test]
+fn route_search_skips_legs_above_max_ratio() {
+    let mut world = setup_weighted_pair();
+    let token_pair = add_test_pair(&mut world, OTHER_BASE_TOKEN, TOKEN, TOKEN_OBASE_LP_TOKEN);
+    add_liquidity(&mut world, OWNER, (OTHER_BASE_TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (OTHER_BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 1);
+    set_pair_active(&mut world, token_pair);
+
+    // 200_000 BASE is above the half of the weighted pair's BASE reserve it can take
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let (path, amount_out) = sc
+            .get_best_route(BASE_TOKEN.to_token_identifier(), TOKEN.to_token_identifier(), BigUint::from(200_000u64), 2)
+            .into_tuple();
+        let path: Vec<String> = path.iter().map(|token| token.to_string()).collect();
+        assert_eq!(path, vec![BASE_TOKEN.as_str(), OTHER_BASE_TOKEN.as_str(), TOKEN.as_str()]);
+        assert!(amount_out > 0u64);
+    });
+}
+
+#[test]
+fn orders_above_max_ratio_are_never_executable() {
+    let mut world = setup_weighted_pair();
+    for amount in [200_000u64, 1_000] {
+        world
+            .tx()
+            .from(USER)
+            .to(DEX)
+            .esdt(TestEsdtTransfer(BASE_TOKEN, 0, amount))
+            .whitebox(tfn_dex::contract_obj, |sc| {
+                sc.place_order(TOKEN.to_token_identifier(), BigUint::from(1u64));
+            });
+    }
+
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        assert!(!sc.can_execute_order(1));
+        assert!(sc.can_execute_order(2));
+    });
+    // the first order is skipped instead of failing the batch
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let order_ids = MultiValueEncoded::from(ManagedVec::from(vec![1u64, 2]));
+        assert_eq!(sc.execute_orders(0, order_ids), 1);
+        assert!(!sc.orders(1).is_empty());
+        assert!(sc.orders(2).is_empty());
+    });
+}
+
+#[test]
+fn weights_must_add_up() {
+    let mut world = setup_dex();
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectError(4, "wrong weight"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.set_pair_weights(0, 8_000, 1_000);
+        });
+}
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+
+multiversx_sc::imports!();
+
+pub const WEGLD_TOKEN_ID: &[u8] = b"WEGLD-123456";
+
+// stand-in for the EGLD wrapper sc: pays out of its own WEGLD and EGLD balances instead of minting and burning
+#[multiversx_sc::contract]
+pub trait WrapperMock {
+    #[init]
+    fn init(&self) {}
+
+    #[payable("EGLD")]
+    #[endpoint(wrapEgld)]
+    fn wrap_egld(&self) {
+        let amount = self.call_value().egld_value().clone_value();
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(&caller, &TokenIdentifier::from(WEGLD_TOKEN_ID), 0, &amount);
+    }
+
+    #[payable("*")]
+    #[endpoint(unwrapEgld)]
+    fn unwrap_egld(&self) {
+        let payment = self.call_value().single_esdt();
+        require!(payment.token_identifier == TokenIdentifier::from(WEGLD_TOKEN_ID), "wrong token");
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_egld(&caller, &payment.amount);
+    }
+}
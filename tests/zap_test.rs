@@ -0,0 +1,94 @@
+mod dex_setup;
+
+use dex_setup::*;
+use multiversx_sc_scenario::imports::*;
+
+use tfn_dex::common::config::*;
+use tfn_dex::zap::ZapModule;
+
+fn setup_token_pair() -> ScenarioWorld {
+    let mut world = setup_dex();
+    add_liquidity(&mut world, OWNER, (TOKEN, 1_000_000), (BASE_TOKEN, 1_000_000));
+    set_pair_active(&mut world, 0);
+
+    world
+}
+
+fn zap_in(world: &mut ScenarioWorld, token_in: TestTokenIdentifier, amount_in: u64) -> u64 {
+    let mut quote = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        quote = sc.get_zap_in_quote(0, token_in.to_token_identifier(), BigUint::from(amount_in)).to_u64().unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(token_in, 0, amount_in))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.zap_in(0, BigUint::from(quote));
+        });
+    world.check_account(USER).esdt_balance(LP_TOKEN, quote);
+
+    quote
+}
+
+#[test]
+fn zap_in_from_base_token_mints_the_quoted_lp() {
+    let mut world = setup_token_pair();
+    let lp_amount = zap_in(&mut world, BASE_TOKEN, 100_000);
+
+    // about half of the deposit in LP terms, minus the fee on the swapped part
+    assert!(lp_amount > 48_000 && lp_amount < 50_000);
+}
+
+#[test]
+fn zap_in_from_token_leaves_only_dust() {
+    let mut world = setup_token_pair();
+    zap_in(&mut world, TOKEN, 100_000);
+
+    // the whole payment ends up in the reserves up to rounding, so next to nothing is refunded
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        let pair = sc.pairs(0).get();
+        assert!(pair.liquidity_token >= 1_100_000u64 - 10);
+        assert!(pair.liquidity_token <= 1_100_000u64);
+    });
+}
+
+#[test]
+fn zap_out_pays_the_quoted_amount_in_one_token() {
+    let mut world = setup_token_pair();
+    let lp_amount = zap_in(&mut world, BASE_TOKEN, 100_000);
+    let mut quote = 0;
+    world.query().to(DEX).whitebox(tfn_dex::contract_obj, |sc| {
+        quote = sc.get_zap_out_quote(0, BASE_TOKEN.to_token_identifier(), BigUint::from(lp_amount)).to_u64().unwrap();
+    });
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, lp_amount))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.zap_out(BASE_TOKEN.to_token_identifier(), BigUint::from(quote));
+        });
+
+    // zapping in and out again only costs the swap fees
+    assert!(quote > 96_000 && quote < 100_000);
+    world.check_account(USER)
+        .esdt_balance(LP_TOKEN, 0u64)
+        .esdt_balance(TOKEN, INITIAL_BALANCE);
+}
+
+#[test]
+fn zap_out_below_min_amount_fails() {
+    let mut world = setup_token_pair();
+    let lp_amount = zap_in(&mut world, TOKEN, 100_000);
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, lp_amount))
+        .returns(ExpectError(4, "insufficient output amount"))
+        .whitebox(tfn_dex::contract_obj, |sc| {
+            sc.zap_out(TOKEN.to_token_identifier(), BigUint::from(100_000u64));
+        });
+}